@@ -0,0 +1,8 @@
+#![no_main]
+
+use apex_legends_api::data_types::ApexProfile;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<ApexProfile>(data);
+});
@@ -0,0 +1,383 @@
+//! A blocking client, for programs that do not use an async runtime.
+//! It has the same methods as [`crate::ApexClient`] and returns the same types.
+//!
+//! Requires the `blocking` feature. It must not be used from within an async runtime.
+//!
+//! # Examples
+//! ```no_run
+//! use apex_legends_api::blocking::ApexClient;
+//!
+//! let client = ApexClient::new("your_api_key");
+//! let user = client.get_user("HeyImLifeline").unwrap();
+//!
+//! println!("{} is level {}", user.global.name, user.global.level);
+//! ```
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    cache::{CacheConfig, CacheStore, ResponseCache},
+    client::{BuilderOptions, Proxy},
+    data_types,
+    games::{GamesQuery, HistoryPages, HistoryStep},
+    http,
+    keys::KeyPool,
+    player_name::PlayerName,
+    request::{is_player_not_found, parse, Attempt, Attempts, Request},
+    Error,
+};
+
+/// Builder for the blocking [`ApexClient`]
+pub struct ApexClientBuilder {
+    options: BuilderOptions,
+}
+
+impl fmt::Debug for ApexClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.options.fmt(f)
+    }
+}
+
+impl ApexClientBuilder {
+    /// See [`crate::client::ApexClientBuilder::add_api_key`]
+    pub fn add_api_key(mut self, api_key: &str) -> Self {
+        self.options.api_keys.push(api_key.to_string());
+        self
+    }
+
+    /// See [`crate::client::ApexClientBuilder::retry`]
+    pub fn retry(mut self, retry: bool) -> Self {
+        self.options.retry = retry;
+        self
+    }
+
+    /// Maximum time to establish a connection with the API. No limit by default.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// See [`crate::client::ApexClientBuilder::read_timeout`]
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.options.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum time for a whole request, from connecting to reading the body. No limit by default.
+    /// Each retry after a 429 error code gets its own time.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// See [`crate::client::ApexClientBuilder::proxy`]
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.options.proxy = Some(proxy);
+        self
+    }
+
+    /// The root of the API, `https://api.mozambiquehe.re` by default.
+    /// Useful to go through a gateway or to test against a mock server.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.options.base_url = base_url.to_string();
+        self
+    }
+
+    /// The `User-Agent` header sent with every request, `apex_legends_api/<version>` by default
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.options.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Extra headers sent with every request
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.options.default_headers.extend(headers);
+        self
+    }
+
+    /// Largest response body accepted, in bytes. Bigger responses fail with `Error::ResponseTooLarge`.
+    /// No limit by default.
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.options.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Cache the responses, see [`CacheConfig`]. Disabled by default.
+    pub fn cache(mut self, config: CacheConfig) -> Self {
+        self.options.cache = Some(config);
+        self
+    }
+
+    /// Where to keep the cached responses, in memory by default.
+    /// Enables the cache with the default [`CacheConfig`] if [`ApexClientBuilder::cache`] is not called.
+    pub fn cache_store<S: CacheStore + 'static>(mut self, store: S) -> Self {
+        self.options.cache_store = Some(Box::new(store));
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// # Panics
+    /// If the HTTP client cannot be created, see [`ApexClientBuilder::try_build`]
+    pub fn build(self) -> ApexClient {
+        self.try_build()
+            .expect("Could not create the HTTP client of ApexClient")
+    }
+
+    /// Builds the client, failing if the HTTP client cannot be created
+    /// (for example when the TLS backend cannot be initialized)
+    pub fn try_build(self) -> Result<ApexClient, Error> {
+        let mut options = self.options;
+
+        // The timeout of the blocking client of reqwest applies to the headers and then to each read of the body,
+        // and is 30 seconds unless told otherwise. The total timeout is set on each request instead.
+        let mut http = reqwest::blocking::Client::builder().timeout(options.read_timeout);
+
+        if let Some(timeout) = options.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+        if let Some(proxy) = options.proxy.take() {
+            http = http.proxy(proxy);
+        }
+
+        let http = http.build().map_err(|e| Error::Request(e.to_string()))?;
+
+        let headers = options.headers()?;
+        let cache = options.response_cache();
+
+        Ok(ApexClient {
+            keys: Arc::new(KeyPool::new(options.api_keys)),
+            retry: options.retry,
+            base_url: options.base_url,
+            headers,
+            timeout: options.timeout,
+            max_response_size: options.max_response_size,
+            http,
+            cache,
+        })
+    }
+}
+
+/// A blocking client for the API. Cloning it is cheap and the clones share the same connection pool and cache.
+#[derive(Clone)]
+pub struct ApexClient {
+    keys: Arc<KeyPool>,
+    retry: bool,
+    base_url: String,
+    /// Sent with every request, besides the API key
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    max_response_size: Option<usize>,
+    http: reqwest::blocking::Client,
+    cache: Option<Arc<ResponseCache>>,
+}
+
+impl fmt::Debug for ApexClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApexClient")
+            .field("api_keys", &self.keys.len())
+            .field("retry", &self.retry)
+            .field("cache", &self.cache.is_some())
+            .finish()
+    }
+}
+
+impl ApexClient {
+    /// Creates a client with a single API key, that retries after a 429 error code and does not cache responses
+    ///
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub fn new(api_key: &str) -> Self {
+        Self::builder(api_key).build()
+    }
+
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub fn builder(api_key: &str) -> ApexClientBuilder {
+        ApexClientBuilder {
+            options: BuilderOptions::new(api_key),
+        }
+    }
+
+    /// Sends a request, see [`Attempts`]
+    fn fetch(&self, request: &Request) -> Result<String, Error> {
+        let mut attempts = Attempts::new(self.keys.clone(), self.retry);
+
+        loop {
+            match attempts.next()? {
+                Attempt::Wait(wait) => std::thread::sleep(wait),
+                Attempt::Send(id, auth) => {
+                    let response = http::get_request_blocking(
+                        &self.http,
+                        request.to_transport(&self.base_url, &self.headers, auth),
+                        self.timeout,
+                        self.max_response_size,
+                    )?;
+
+                    if let Some(result) = attempts.finish(id, response) {
+                        return result;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the cached response of a request or sends it, caching the response if it is valid.
+    /// The inner error is the one reported by the API in a 200 OK response
+    fn try_get<T: DeserializeOwned>(&self, request: Request) -> Result<Result<T, Error>, Error> {
+        let key = request.key();
+
+        if let Some(cache) = &self.cache {
+            if let Some(Ok(Ok(data))) = cache.get(&key).map(|body| parse(&body)) {
+                return Ok(Ok(data));
+            }
+        }
+
+        let body = self.fetch(&request)?;
+        let data = parse(&body)?;
+
+        if let (Some(cache), Ok(_)) = (&self.cache, &data) {
+            cache.insert(request.endpoint, &key, body);
+        }
+
+        Ok(data)
+    }
+
+    fn get<T: DeserializeOwned>(&self, request: Request) -> Result<T, Error> {
+        self.try_get(request)?
+    }
+
+    /// Gets information about a PC player.
+    /// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
+    ///
+    /// # Parameters
+    /// * `username` - The Origin username of the player, see [`PlayerName`]
+    pub fn get_user(&self, username: &str) -> Result<data_types::ApexUser, Error> {
+        self.get_user_on_platform(username, data_types::Platform::Pc)
+    }
+
+    /// Gets information about a player on the given platform.
+    /// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
+    ///
+    /// # Parameters
+    /// * `username` - The name of the player on the platform, see [`PlayerName`]
+    /// * `platform` - The platform of the player
+    pub fn get_user_on_platform(
+        &self,
+        username: &str,
+        platform: data_types::Platform,
+    ) -> Result<data_types::ApexUser, Error> {
+        let username = PlayerName::new(username)?;
+
+        self.get(Request::user(&username, platform))
+    }
+
+    /// Gets the games matching a query.
+    /// You must be whitelisted to use this API. It has a strict limit of 5 uniques players queried per hour.
+    /// See [https://apexlegendsapi.com/#match-history](https://apexlegendsapi.com/#match-history)
+    ///
+    /// # Parameters
+    /// * `query` - The player's UID and the filters to apply
+    pub fn get_games(&self, query: &GamesQuery) -> Result<Vec<data_types::ApexGame>, Error> {
+        self.get(Request::games(query))
+    }
+
+    /// Gets every recent game of a player.
+    /// See [`ApexClient::get_games`]
+    ///
+    /// # Parameters
+    /// * `user_id` - The player's UID
+    pub fn get_recent_games(&self, user_id: &str) -> Result<Vec<data_types::ApexGame>, Error> {
+        self.get_games(&GamesQuery::new(user_id))
+    }
+
+    /// Walks a player's match history back to the `until` Unix timestamp.
+    /// See [`crate::games::history`]
+    pub fn games_history(
+        &self,
+        query: GamesQuery,
+        until: i64,
+    ) -> impl Iterator<Item = Result<data_types::ApexGame, Error>> {
+        let client = self.clone();
+        let mut pages = HistoryPages::new(query, until);
+
+        std::iter::from_fn(move || loop {
+            match pages.next_step() {
+                HistoryStep::Game(game) => return Some(Ok(*game)),
+                HistoryStep::Done => return None,
+                HistoryStep::Fetch(query) => match client.get_games(&query) {
+                    Ok(page) => pages.push_page(page),
+                    Err(e) => {
+                        pages.stop();
+                        return Some(Err(e));
+                    }
+                },
+            }
+        })
+    }
+
+    /// Returns a player's UID from a given name on the given platform.
+    /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+    ///
+    /// # Parameters
+    /// * `username` - The player's name, see [`PlayerName`]
+    /// * `platform` - The platform of the player
+    pub fn get_uid_from_username(
+        &self,
+        username: &str,
+        platform: data_types::Platform,
+    ) -> Result<data_types::ApexProfile, Error> {
+        let username = PlayerName::new(username)?;
+
+        self.get(Request::name_to_uid(&username, platform))
+    }
+
+    /// Looks up a name on every platform and returns all the matches, each with the platform it was found on.
+    /// An empty list means the name was not found anywhere, any other error of the API is returned.
+    /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+    ///
+    /// # Parameters
+    /// * `username` - The player's name, see [`PlayerName`]
+    pub fn find_uids_from_username(
+        &self,
+        username: &str,
+    ) -> Result<Vec<data_types::PlatformProfile>, Error> {
+        let username = PlayerName::new(username)?;
+        let mut found = Vec::new();
+
+        for platform in data_types::Platform::ALL.iter().copied() {
+            let request = Request::name_to_uid(&username, platform);
+
+            match self.try_get(request)? {
+                Ok(profile) => found.push(data_types::PlatformProfile { platform, profile }),
+                // The API reports players that do not exist with a 200 OK and an error message
+                Err(e) if is_player_not_found(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// The map rotation API will return the current and next map for Battle Royale and Arenas, for both pubs and ranked modes.
+    /// See [https://apexlegendsapi.com/#map-rotation](https://apexlegendsapi.com/#map-rotation)
+    pub fn get_map_rotation(&self) -> Result<data_types::ApexMapRotation, Error> {
+        self.get(Request::map_rotation())
+    }
+
+    /// Returns the status of Origin login, EA accounts, crossplay auth and the Apex Legends servers in every region.
+    /// See [https://apexlegendsapi.com/#server-status](https://apexlegendsapi.com/#server-status)
+    pub fn get_server_status(&self) -> Result<data_types::ServerStatus, Error> {
+        self.get(Request::server_status())
+    }
+
+    /// Returns the points needed to reach Apex Predator and the number of Masters and Predators,
+    /// for Battle Royale and Arenas on every platform.
+    /// See [https://apexlegendsapi.com/#predator](https://apexlegendsapi.com/#predator)
+    pub fn get_predator_thresholds(&self) -> Result<data_types::PredatorThresholds, Error> {
+        self.get(Request::predator())
+    }
+}
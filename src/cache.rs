@@ -0,0 +1,260 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{client::Endpoint, data_types::ApexMapRotation, write_atomic};
+
+/// Time to live of the cached responses of each endpoint.
+/// Endpoints without a TTL are never cached.
+///
+/// Map rotation responses expire when the current map of any mode changes
+/// (see [`ApexMapRotation::next_change`]) or after their TTL, whichever happens first.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use apex_legends_api::{cache::CacheConfig, client::Endpoint};
+///
+/// let config = CacheConfig::default()
+///     .ttl(Endpoint::Player, Duration::from_secs(30))
+///     .disable(Endpoint::MatchHistory);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    ttls: HashMap<Endpoint, Duration>,
+}
+
+impl CacheConfig {
+    /// A configuration that does not cache any endpoint
+    pub fn empty() -> Self {
+        Self {
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Cache the responses of `endpoint` for `ttl`
+    pub fn ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// Never cache the responses of `endpoint`
+    pub fn disable(mut self, endpoint: Endpoint) -> Self {
+        self.ttls.remove(&endpoint);
+        self
+    }
+
+    /// Returns the TTL of `endpoint`, if it is cached
+    pub fn get_ttl(&self, endpoint: Endpoint) -> Option<Duration> {
+        self.ttls.get(&endpoint).copied()
+    }
+}
+
+impl Default for CacheConfig {
+    /// Player statistics, match history and server status are cached for a minute,
+    /// name lookups for an hour, predator thresholds for 10 minutes
+    /// and the map rotation until it changes (at most an hour).
+    fn default() -> Self {
+        Self::empty()
+            .ttl(Endpoint::Player, Duration::from_secs(60))
+            .ttl(Endpoint::MatchHistory, Duration::from_secs(60))
+            .ttl(Endpoint::NameToUid, Duration::from_secs(60 * 60))
+            .ttl(Endpoint::MapRotation, Duration::from_secs(60 * 60))
+            .ttl(Endpoint::ServerStatus, Duration::from_secs(60))
+            .ttl(Endpoint::Predator, Duration::from_secs(10 * 60))
+    }
+}
+
+/// A cached response body
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedResponse {
+    pub body: String,
+    /// Unix timestamp in milliseconds after which the response must not be used
+    pub expires_at_ms: u64,
+}
+
+impl CachedResponse {
+    /// Whether the response has expired
+    pub fn is_expired(&self) -> bool {
+        unix_now_ms() >= self.expires_at_ms
+    }
+}
+
+/// In milliseconds, so TTLs under a second are kept
+fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Storage backend of the response cache.
+/// Keys are made of the endpoint and the parameters of the request, never the API key.
+///
+/// Errors are not reported: a store that fails to save or load a response
+/// should behave as if it was not cached.
+pub trait CacheStore: Send + Sync {
+    /// Returns the response stored for `key`, even if it has expired
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+    /// Stores a response, replacing the previous one
+    fn put(&self, key: &str, response: CachedResponse);
+    /// Removes the response stored for `key`, if any
+    fn remove(&self, key: &str);
+}
+
+/// Keeps the responses in memory. They are lost when the program exits.
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries.lock().ok()?.get(key).cloned()
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.to_string(), response);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(key);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileEntry {
+    key: String,
+    #[serde(flatten)]
+    response: CachedResponse,
+}
+
+/// Keeps every response as a JSON file in a directory, so the cache survives restarts.
+///
+/// # Examples
+/// ```
+/// # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+/// use apex_legends_api::{cache::{CacheConfig, FileStore}, ApexClient};
+///
+/// # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+/// let client = ApexClient::builder("your_api_key")
+///     .cache(CacheConfig::default())
+///     .cache_store(FileStore::new(std::env::temp_dir().join("apex_cache")))
+///     .build();
+/// ```
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// Stores the responses in `dir`, which is created when the first response is saved
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The file of a key is named after its FNV-1a hash, the key itself is stored inside
+    fn path(&self, key: &str) -> PathBuf {
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+        self.dir.join(format!("{:016x}.json", hash))
+    }
+}
+
+impl CacheStore for FileStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let content = fs::read_to_string(self.path(key)).ok()?;
+        let entry: FileEntry = serde_json::from_str(&content).ok()?;
+
+        if entry.key == key {
+            Some(entry.response)
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        let entry = FileEntry {
+            key: key.to_string(),
+            response,
+        };
+
+        if let Ok(bytes) = serde_json::to_vec(&entry) {
+            let _ = write_atomic(&self.path(key), &bytes);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path(key));
+    }
+}
+
+/// Cache of raw response bodies, keyed by endpoint and parameters
+pub(crate) struct ResponseCache {
+    config: CacheConfig,
+    store: Box<dyn CacheStore>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(config: CacheConfig, store: Box<dyn CacheStore>) -> Self {
+        Self { config, store }
+    }
+
+    /// Returns the cached body for `key`, if it has not expired yet
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        let response = self.store.get(key)?;
+
+        if response.is_expired() {
+            self.store.remove(key);
+            None
+        } else {
+            Some(response.body)
+        }
+    }
+
+    /// Stores a successful response, if `endpoint` is cached
+    pub(crate) fn insert(&self, endpoint: Endpoint, key: &str, body: String) {
+        let ttl = match self.config.get_ttl(endpoint) {
+            Some(ttl) => ttl,
+            None => return,
+        };
+
+        let mut expires_at_ms = unix_now_ms().saturating_add(ttl.as_millis() as u64);
+
+        if endpoint == Endpoint::MapRotation {
+            let next_change = serde_json::from_str::<ApexMapRotation>(&body)
+                .map(|rotation| rotation.next_change())
+                .unwrap_or(0);
+
+            // 0 when no mode has an end, e.g. in a partial response, then only the TTL applies
+            if next_change > 0 {
+                expires_at_ms = expires_at_ms.min(next_change as u64 * 1000);
+            }
+        }
+
+        self.store.put(
+            key,
+            CachedResponse {
+                body,
+                expires_at_ms,
+            },
+        );
+    }
+}
@@ -0,0 +1,688 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+
+use crate::{
+    cache::{CacheConfig, CacheStore, MemoryStore, ResponseCache},
+    request::{BASE_URL, DEFAULT_USER_AGENT},
+    Error,
+};
+
+cfg_runtime! {
+    use std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+        sync::Mutex,
+    };
+
+    use futures::{
+        future::{BoxFuture, FutureExt, Shared},
+        stream::Stream,
+    };
+    use serde::de::DeserializeOwned;
+
+    use crate::{
+        data_types, games,
+        games::GamesQuery,
+        keys::KeyPool,
+        player_name::PlayerName,
+        request::{is_player_not_found, parse, Attempt, Attempts, Request},
+        rt,
+        transport::{ReqwestTransport, Transport},
+        vcr::{Recorder, Replayer},
+        watch::{MapRotationEvent, MapRotationWatcher},
+    };
+}
+
+pub use reqwest::{header, Proxy};
+
+/// An endpoint of the API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// `/bridge`, the player statistics
+    Player,
+    /// `/games`, the match history
+    MatchHistory,
+    /// `/nametouid`
+    NameToUid,
+    /// `/maprotation`
+    MapRotation,
+    /// `/servers`
+    ServerStatus,
+    /// `/predator`
+    Predator,
+}
+
+impl Endpoint {
+    /// The path of the endpoint, relative to the API root
+    pub fn path(&self) -> &'static str {
+        match self {
+            Endpoint::Player => "bridge",
+            Endpoint::MatchHistory => "games",
+            Endpoint::NameToUid => "nametouid",
+            Endpoint::MapRotation => "maprotation",
+            Endpoint::ServerStatus => "servers",
+            Endpoint::Predator => "predator",
+        }
+    }
+}
+
+/// Debug output that never contains the API key
+fn redacted(api_key: &str) -> &'static str {
+    if api_key.is_empty() {
+        ""
+    } else {
+        "<redacted>"
+    }
+}
+
+/// The options set on the async and the blocking `ApexClientBuilder`,
+/// each builder only turns them into its own client
+pub(crate) struct BuilderOptions {
+    pub(crate) api_keys: Vec<String>,
+    pub(crate) retry: bool,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) read_timeout: Option<Duration>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) proxy: Option<Proxy>,
+    pub(crate) base_url: String,
+    pub(crate) user_agent: String,
+    pub(crate) default_headers: HeaderMap,
+    pub(crate) max_response_size: Option<usize>,
+    pub(crate) cache: Option<CacheConfig>,
+    pub(crate) cache_store: Option<Box<dyn CacheStore>>,
+}
+
+impl BuilderOptions {
+    /// The defaults of both builders
+    pub(crate) fn new(api_key: &str) -> Self {
+        Self {
+            api_keys: vec![api_key.to_string()],
+            retry: true,
+            connect_timeout: None,
+            read_timeout: None,
+            timeout: None,
+            proxy: None,
+            base_url: BASE_URL.to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            default_headers: HeaderMap::new(),
+            max_response_size: None,
+            cache: None,
+            cache_store: None,
+        }
+    }
+
+    /// The headers sent with every request besides the API key,
+    /// failing if the user agent is not a valid header value
+    pub(crate) fn headers(&self) -> Result<HeaderMap, Error> {
+        let mut headers = self.default_headers.clone();
+        let user_agent = HeaderValue::from_str(&self.user_agent)
+            .map_err(|e| Error::Request(format!("Invalid user agent: {}", e)))?;
+        headers.insert(USER_AGENT, user_agent);
+
+        Ok(headers)
+    }
+
+    /// The response cache, if either the config or the store was set
+    pub(crate) fn response_cache(&mut self) -> Option<Arc<ResponseCache>> {
+        match (self.cache.take(), self.cache_store.take()) {
+            (None, None) => None,
+            (config, store) => Some(Arc::new(ResponseCache::new(
+                config.unwrap_or_default(),
+                store.unwrap_or_else(|| Box::new(MemoryStore::new())),
+            ))),
+        }
+    }
+}
+
+// Both builders are named `ApexClientBuilder` and print their options as is
+impl fmt::Debug for BuilderOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApexClientBuilder")
+            .field(
+                "api_keys",
+                &self
+                    .api_keys
+                    .iter()
+                    .map(|k| redacted(k))
+                    .collect::<Vec<_>>(),
+            )
+            .field("retry", &self.retry)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("read_timeout", &self.read_timeout)
+            .field("timeout", &self.timeout)
+            .field("proxy", &self.proxy.is_some())
+            .field("base_url", &self.base_url)
+            .field("user_agent", &self.user_agent)
+            .field(
+                "default_headers",
+                &self.default_headers.keys().collect::<Vec<_>>(),
+            )
+            .field("max_response_size", &self.max_response_size)
+            .field("cache", &self.cache)
+            .field("cache_store", &self.cache_store.is_some())
+            .finish()
+    }
+}
+
+cfg_runtime! {
+    /// Sends a request, see [`Attempts`]
+    async fn send_request<T: Transport>(
+        transport: Arc<T>,
+        request: Request,
+        base_url: String,
+        headers: HeaderMap,
+        keys: Arc<KeyPool>,
+        retry: bool,
+    ) -> Result<String, Error> {
+        let mut attempts = Attempts::new(keys, retry);
+
+        loop {
+            match attempts.next()? {
+                Attempt::Wait(wait) => rt::sleep(wait).await,
+                Attempt::Send(id, auth) => {
+                    let response = transport
+                        .send(request.to_transport(&base_url, &headers, auth))
+                        .await?;
+
+                    if let Some(result) = attempts.finish(id, response) {
+                        return result;
+                    }
+                }
+            }
+        }
+    }
+
+    /// A request shared by every caller waiting for the same response
+    type InFlight = Shared<BoxFuture<'static, Result<String, Error>>>;
+
+    /// Builder for [`ApexClient`]
+    pub struct ApexClientBuilder {
+        options: BuilderOptions,
+    }
+
+    impl fmt::Debug for ApexClientBuilder {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.options.fmt(f)
+        }
+    }
+
+    impl ApexClientBuilder {
+        /// Adds another API key. Requests are spread across all the keys in turns,
+        /// a key that hits the rate limit is skipped until it can be used again
+        /// and a key rejected by the API is not used anymore.
+        pub fn add_api_key(mut self, api_key: &str) -> Self {
+            self.options.api_keys.push(api_key.to_string());
+            self
+        }
+
+        /// Wether to wait and retry when every API key got a 429 error code (too many requests),
+        /// for the time given by the `x-current-rate` header or DEFAULT_RATE.
+        /// A request that gets a 429 is always sent again with the next key that is not rate limited.
+        /// Enabled by default.
+        pub fn retry(mut self, retry: bool) -> Self {
+            self.options.retry = retry;
+            self
+        }
+
+        /// Maximum time to establish a connection with the API. No limit by default.
+        /// Only applies to the default transport.
+        pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+            self.options.connect_timeout = Some(timeout);
+            self
+        }
+
+        /// Maximum time to wait for the response headers, and then for the body. No limit by default.
+        /// Only applies to the default transport.
+        pub fn read_timeout(mut self, timeout: Duration) -> Self {
+            self.options.read_timeout = Some(timeout);
+            self
+        }
+
+        /// Maximum time for a whole request, from connecting to reading the body. No limit by default.
+        /// Each retry after a 429 error code gets its own time.
+        /// Only applies to the default transport.
+        pub fn timeout(mut self, timeout: Duration) -> Self {
+            self.options.timeout = Some(timeout);
+            self
+        }
+
+        /// Sends the requests through an HTTP, HTTPS or (with the `socks` feature) SOCKS proxy.
+        /// By default the system proxy is used. Only applies to the default transport.
+        ///
+        /// # Examples
+        /// ```
+        /// use apex_legends_api::{client::Proxy, ApexClient};
+        ///
+        /// let client = ApexClient::builder("your_api_key")
+        ///     .proxy(Proxy::all("http://localhost:8080").unwrap())
+        ///     .build();
+        /// ```
+        pub fn proxy(mut self, proxy: Proxy) -> Self {
+            self.options.proxy = Some(proxy);
+            self
+        }
+
+        /// The root of the API, `https://api.mozambiquehe.re` by default.
+        /// Useful to go through a gateway or to test against a mock server.
+        pub fn base_url(mut self, base_url: &str) -> Self {
+            self.options.base_url = base_url.to_string();
+            self
+        }
+
+        /// The `User-Agent` header sent with every request, `apex_legends_api/<version>` by default
+        pub fn user_agent(mut self, user_agent: &str) -> Self {
+            self.options.user_agent = user_agent.to_string();
+            self
+        }
+
+        /// Extra headers sent with every request
+        pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+            self.options.default_headers.extend(headers);
+            self
+        }
+
+        /// Largest response body accepted, in bytes. Bigger responses fail with `Error::ResponseTooLarge`.
+        /// No limit by default. Only applies to the default transport.
+        pub fn max_response_size(mut self, bytes: usize) -> Self {
+            self.options.max_response_size = Some(bytes);
+            self
+        }
+
+        /// Cache the responses, see [`CacheConfig`]. Disabled by default.
+        pub fn cache(mut self, config: CacheConfig) -> Self {
+            self.options.cache = Some(config);
+            self
+        }
+
+        /// Where to keep the cached responses, in memory by default.
+        /// Enables the cache with the default [`CacheConfig`] if [`ApexClientBuilder::cache`] is not called.
+        pub fn cache_store<S: CacheStore + 'static>(mut self, store: S) -> Self {
+            self.options.cache_store = Some(Box::new(store));
+            self
+        }
+
+        /// Builds the client.
+        ///
+        /// # Panics
+        /// If the HTTP client cannot be created, see [`ApexClientBuilder::try_build`]
+        pub fn build(self) -> ApexClient {
+            self.try_build()
+                .expect("Could not create the HTTP client of ApexClient")
+        }
+
+        /// Builds the client, failing if the HTTP client cannot be created
+        /// (for example when the TLS backend cannot be initialized)
+        pub fn try_build(mut self) -> Result<ApexClient, Error> {
+            let transport = self.reqwest_transport()?;
+
+            self.try_build_with_transport(transport)
+        }
+
+        /// Builds a client that records every request and response in `cassette`, see [`Recorder`]
+        pub fn build_recording<P: Into<PathBuf>>(
+            mut self,
+            cassette: P,
+        ) -> Result<ApexClient<Recorder<ReqwestTransport>>, Error> {
+            let transport = self.reqwest_transport()?;
+
+            self.try_build_with_transport(Recorder::new(transport, cassette))
+        }
+
+        /// Builds a client that answers with the responses recorded in `cassette`
+        /// and never uses the network, see [`Replayer`]
+        pub fn build_replaying<P: AsRef<Path>>(
+            self,
+            cassette: P,
+        ) -> Result<ApexClient<Replayer>, Error> {
+            let transport = Replayer::from_file(cassette)?;
+
+            self.try_build_with_transport(transport)
+        }
+
+        /// The default transport, with the options of this builder
+        fn reqwest_transport(&mut self) -> Result<ReqwestTransport, Error> {
+            let mut http = reqwest::Client::builder();
+
+            if let Some(timeout) = self.options.connect_timeout {
+                http = http.connect_timeout(timeout);
+            }
+            if let Some(timeout) = self.options.timeout {
+                http = http.timeout(timeout);
+            }
+            if let Some(proxy) = self.options.proxy.take() {
+                http = http.proxy(proxy);
+            }
+
+            let http = http.build().map_err(|e| Error::Request(e.to_string()))?;
+
+            let mut transport = ReqwestTransport::new(http);
+            if let Some(timeout) = self.options.read_timeout {
+                transport = transport.read_timeout(timeout);
+            }
+            if let Some(bytes) = self.options.max_response_size {
+                transport = transport.max_response_size(bytes);
+            }
+
+            Ok(transport)
+        }
+
+        /// Builds a client that sends its requests through `transport`, see [`crate::transport`].
+        ///
+        /// # Panics
+        /// If the user agent is not a valid header value
+        pub fn build_with_transport<T: Transport>(self, transport: T) -> ApexClient<T> {
+            self.try_build_with_transport(transport)
+                .expect("Could not create the HTTP client of ApexClient")
+        }
+
+        /// Builds a client that sends its requests through `transport`,
+        /// failing if the user agent is not a valid header value
+        pub fn try_build_with_transport<T: Transport>(
+            self,
+            transport: T,
+        ) -> Result<ApexClient<T>, Error> {
+            let mut options = self.options;
+            let headers = options.headers()?;
+            let cache = options.response_cache();
+
+            Ok(ApexClient {
+                keys: Arc::new(KeyPool::new(options.api_keys)),
+                retry: options.retry,
+                base_url: options.base_url,
+                headers,
+                transport: Arc::new(transport),
+                cache,
+                in_flight: Arc::new(Mutex::new(HashMap::new())),
+            })
+        }
+    }
+
+    /// A client for the API. Cloning it is cheap and the clones share the same transport and cache.
+    ///
+    /// Identical requests made at the same time are only sent once, and every caller gets a copy of the response.
+    ///
+    /// # Examples
+    /// ```
+    /// use apex_legends_api::{cache::CacheConfig, ApexClient};
+    ///
+    /// async fn level(api_key: &str) -> Result<i32, apex_legends_api::Error> {
+    ///     let client = ApexClient::builder(api_key)
+    ///         .cache(CacheConfig::default())
+    ///         .build();
+    ///
+    ///     Ok(client.get_user("HeyImLifeline").await?.global.level)
+    /// }
+    /// ```
+    pub struct ApexClient<T = ReqwestTransport> {
+        keys: Arc<KeyPool>,
+        retry: bool,
+        base_url: String,
+        /// Sent with every request, besides the API key
+        headers: HeaderMap,
+        transport: Arc<T>,
+        cache: Option<Arc<ResponseCache>>,
+        in_flight: Arc<Mutex<HashMap<String, InFlight>>>,
+    }
+
+    impl<T> Clone for ApexClient<T> {
+        fn clone(&self) -> Self {
+            Self {
+                keys: self.keys.clone(),
+                retry: self.retry,
+                base_url: self.base_url.clone(),
+                headers: self.headers.clone(),
+                transport: self.transport.clone(),
+                cache: self.cache.clone(),
+                in_flight: self.in_flight.clone(),
+            }
+        }
+    }
+
+    impl<T> fmt::Debug for ApexClient<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ApexClient")
+                .field("api_keys", &self.keys.len())
+                .field("retry", &self.retry)
+                .field("cache", &self.cache.is_some())
+                .finish()
+        }
+    }
+
+    impl ApexClient {
+        /// Creates a client with a single API key, that retries after a 429 error code and does not cache responses
+        ///
+        /// # Parameters
+        /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+        pub fn new(api_key: &str) -> Self {
+            Self::builder(api_key).build()
+        }
+
+        /// # Parameters
+        /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+        pub fn builder(api_key: &str) -> ApexClientBuilder {
+            ApexClientBuilder {
+                options: BuilderOptions::new(api_key),
+            }
+        }
+    }
+
+    impl<T: Transport> ApexClient<Recorder<T>> {
+        /// Writes the traffic recorded so far to the cassette, see [`ApexClientBuilder::build_recording`].
+        /// It is also written when the last clone of the client is dropped.
+        pub fn save_cassette(&self) -> Result<(), Error> {
+            self.transport.save()
+        }
+    }
+
+    impl<T: Transport> ApexClient<T> {
+        /// Sends a request, or waits for the identical one that is already in flight
+        async fn fetch(&self, key: &str, request: &Request) -> Result<String, Error> {
+            let in_flight_request = {
+                let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+
+                match in_flight.get(key) {
+                    Some(in_flight_request) => in_flight_request.clone(),
+                    None => {
+                        let in_flight_request = send_request(
+                            self.transport.clone(),
+                            request.clone(),
+                            self.base_url.clone(),
+                            self.headers.clone(),
+                            self.keys.clone(),
+                            self.retry,
+                        )
+                        .boxed()
+                        .shared();
+                        in_flight.insert(key.to_string(), in_flight_request.clone());
+
+                        in_flight_request
+                    }
+                }
+            };
+
+            let result = in_flight_request.clone().await;
+
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            if matches!(in_flight.get(key), Some(r) if r.ptr_eq(&in_flight_request)) {
+                in_flight.remove(key);
+            }
+
+            result
+        }
+
+        /// Returns the cached response of a request or sends it, caching the response if it is valid.
+        /// The inner error is the one reported by the API in a 200 OK response
+        async fn try_get<D: DeserializeOwned>(
+            &self,
+            request: Request,
+        ) -> Result<Result<D, Error>, Error> {
+            let key = request.key();
+
+            if let Some(cache) = &self.cache {
+                if let Some(Ok(Ok(data))) = cache.get(&key).map(|body| parse(&body)) {
+                    return Ok(Ok(data));
+                }
+            }
+
+            self.try_refresh(request).await
+        }
+
+        /// Sends the request even when its response is cached, and caches the new response.
+        async fn try_refresh<D: DeserializeOwned>(
+            &self,
+            request: Request,
+        ) -> Result<Result<D, Error>, Error> {
+            let key = request.key();
+            let body = self.fetch(&key, &request).await?;
+            let data = parse(&body)?;
+
+            if let (Some(cache), Ok(_)) = (&self.cache, &data) {
+                cache.insert(request.endpoint, &key, body);
+            }
+
+            Ok(data)
+        }
+
+        async fn get<D: DeserializeOwned>(&self, request: Request) -> Result<D, Error> {
+            self.try_get(request).await?
+        }
+
+        /// Gets information about a PC player.
+        /// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
+        ///
+        /// # Parameters
+        /// * `username` - The Origin username of the player, see [`PlayerName`]
+        pub async fn get_user(&self, username: &str) -> Result<data_types::ApexUser, Error> {
+            self.get_user_on_platform(username, data_types::Platform::Pc)
+                .await
+        }
+
+        /// Gets information about a player on the given platform.
+        /// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
+        ///
+        /// # Parameters
+        /// * `username` - The name of the player on the platform, see [`PlayerName`]
+        /// * `platform` - The platform of the player
+        pub async fn get_user_on_platform(
+            &self,
+            username: &str,
+            platform: data_types::Platform,
+        ) -> Result<data_types::ApexUser, Error> {
+            let username = PlayerName::new(username)?;
+
+            self.get(Request::user(&username, platform)).await
+        }
+
+        /// Like [`ApexClient::get_user_on_platform`], but never answered from the response cache.
+        pub(crate) async fn get_fresh_user_on_platform(
+            &self,
+            username: &str,
+            platform: data_types::Platform,
+        ) -> Result<data_types::ApexUser, Error> {
+            let username = PlayerName::new(username)?;
+
+            self.try_refresh(Request::user(&username, platform)).await?
+        }
+
+        /// Gets the games matching a query.
+        /// You must be whitelisted to use this API. It has a strict limit of 5 uniques players queried per hour.
+        /// See [https://apexlegendsapi.com/#match-history](https://apexlegendsapi.com/#match-history)
+        ///
+        /// # Parameters
+        /// * `query` - The player's UID and the filters to apply
+        pub async fn get_games(&self, query: &GamesQuery) -> Result<Vec<data_types::ApexGame>, Error> {
+            self.get(Request::games(query)).await
+        }
+
+        /// Gets every recent game of a player.
+        /// See [`ApexClient::get_games`]
+        ///
+        /// # Parameters
+        /// * `user_id` - The player's UID
+        pub async fn get_recent_games(
+            &self,
+            user_id: &str,
+        ) -> Result<Vec<data_types::ApexGame>, Error> {
+            self.get_games(&GamesQuery::new(user_id)).await
+        }
+
+        /// Walks a player's match history back to the `until` Unix timestamp.
+        /// See [`games::history`]
+        pub fn games_history(
+            &self,
+            query: GamesQuery,
+            until: i64,
+        ) -> impl Stream<Item = Result<data_types::ApexGame, Error>> {
+            games::history_stream(self.clone(), query, until)
+        }
+
+        /// Returns a player's UID from a given name on the given platform.
+        /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+        ///
+        /// # Parameters
+        /// * `username` - The player's name, see [`PlayerName`]
+        /// * `platform` - The platform of the player
+        pub async fn get_uid_from_username(
+            &self,
+            username: &str,
+            platform: data_types::Platform,
+        ) -> Result<data_types::ApexProfile, Error> {
+            let username = PlayerName::new(username)?;
+
+            self.get(Request::name_to_uid(&username, platform)).await
+        }
+
+        /// Looks up a name on every platform and returns all the matches, each with the platform it was found on.
+        /// An empty list means the name was not found anywhere, any other error of the API is returned.
+        /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+        ///
+        /// # Parameters
+        /// * `username` - The player's name, see [`PlayerName`]
+        pub async fn find_uids_from_username(
+            &self,
+            username: &str,
+        ) -> Result<Vec<data_types::PlatformProfile>, Error> {
+            let username = PlayerName::new(username)?;
+            let mut found = Vec::new();
+
+            for platform in data_types::Platform::ALL.iter().copied() {
+                let request = Request::name_to_uid(&username, platform);
+
+                match self.try_get(request).await? {
+                    Ok(profile) => found.push(data_types::PlatformProfile { platform, profile }),
+                    // The API reports players that do not exist with a 200 OK and an error message
+                    Err(e) if is_player_not_found(&e) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            Ok(found)
+        }
+
+        /// The map rotation API will return the current and next map for Battle Royale and Arenas, for both pubs and ranked modes.
+        /// See [https://apexlegendsapi.com/#map-rotation](https://apexlegendsapi.com/#map-rotation)
+        pub async fn get_map_rotation(&self) -> Result<data_types::ApexMapRotation, Error> {
+            self.get(Request::map_rotation()).await
+        }
+
+        /// Yields an event whenever the current map of a mode changes.
+        /// See [`MapRotationWatcher`] to change how long it waits between requests.
+        pub fn watch_map_rotation(&self) -> impl Stream<Item = Result<MapRotationEvent, Error>> {
+            MapRotationWatcher::new(self.clone()).stream()
+        }
+
+        /// Returns the status of Origin login, EA accounts, crossplay auth and the Apex Legends servers in every region.
+        /// See [https://apexlegendsapi.com/#server-status](https://apexlegendsapi.com/#server-status)
+        pub async fn get_server_status(&self) -> Result<data_types::ServerStatus, Error> {
+            self.get(Request::server_status()).await
+        }
+
+        /// Returns the points needed to reach Apex Predator and the number of Masters and Predators,
+        /// for Battle Royale and Arenas on every platform.
+        /// See [https://apexlegendsapi.com/#predator](https://apexlegendsapi.com/#predator)
+        pub async fn get_predator_thresholds(&self) -> Result<data_types::PredatorThresholds, Error> {
+            self.get(Request::predator()).await
+        }
+    }
+}
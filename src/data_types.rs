@@ -1,224 +1,606 @@
-use serde::Deserialize;
+use std::{collections::HashMap, fmt, str::FromStr};
 
-#[derive(Deserialize)]
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::Value;
+
+use crate::lenient;
+
+// The fields that do not always have the same shape are parsed leniently (see `crate::lenient`),
+// only the fields that identify the data are required.
+
+#[derive(Serialize, Deserialize)]
 pub struct ApexUser {
     pub global: ApexGlobal,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub realtime: ApexRealtime,
-    #[serde(alias = "total")]
+    #[serde(alias = "total", default, deserialize_with = "lenient::or_default")]
     pub stats: ApexStats,
 }
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ApexGlobal {
+    #[serde(deserialize_with = "lenient::string")]
     pub name: String,
+    #[serde(deserialize_with = "lenient::int")]
     pub uid: i64,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub avatar: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub platform: String,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub level: i32,
-    #[serde(alias = "levelPrestige")]
+    #[serde(alias = "levelPrestige", default, deserialize_with = "lenient::int")]
     pub level_prestige: i32,
-    #[serde(alias = "toNextLevelPercent")]
+    #[serde(
+        alias = "toNextLevelPercent",
+        default,
+        deserialize_with = "lenient::int"
+    )]
     pub to_next_level_percent: i32,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub rank: ApexRank,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub arena: ApexRank,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub bans: ApexBans,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub battlepass: ApexBattlepass,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct ApexRealtime {
-    #[serde(alias = "lobbyState")]
+    #[serde(alias = "lobbyState", default, deserialize_with = "lenient::string")]
     pub lobby_state: String,
-    #[serde(alias = "isOnline")]
+    #[serde(alias = "isOnline", default, deserialize_with = "lenient::int")]
     pub is_online: i32,
-    #[serde(alias = "isInGame")]
+    #[serde(alias = "isInGame", default, deserialize_with = "lenient::int")]
     pub is_in_game: i32,
-    #[serde(alias = "canJoin")]
+    #[serde(alias = "canJoin", default, deserialize_with = "lenient::int")]
     pub can_join: i32,
-    #[serde(alias = "partyFull")]
+    #[serde(alias = "partyFull", default, deserialize_with = "lenient::int")]
     pub party_full: i32,
-    #[serde(alias = "selectedLegend")]
+    #[serde(
+        alias = "selectedLegend",
+        default,
+        deserialize_with = "lenient::string"
+    )]
     pub selected_legend: String,
-    #[serde(alias = "currentState")]
+    #[serde(alias = "currentState", default, deserialize_with = "lenient::string")]
     pub current_state: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct ApexBattlepass {
+    #[serde(default, deserialize_with = "lenient::string")]
     pub level: String,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub history: ApexBattlepassHistory,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct ApexBattlepassHistory {
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season1: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season2: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season3: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season4: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season5: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season6: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season7: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season8: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season9: i32,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub season10: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct ApexRank {
-    #[serde(alias = "rankScore")]
+    #[serde(alias = "rankScore", default, deserialize_with = "lenient::int")]
     pub rank_score: i32,
-    #[serde(alias = "rankName")]
+    #[serde(alias = "rankName", default, deserialize_with = "lenient::string")]
     pub rank_name: String,
-    #[serde(alias = "rankDiv")]
+    #[serde(alias = "rankDiv", default, deserialize_with = "lenient::int")]
     pub rank_division: i32,
-    #[serde(alias = "rankImg")]
+    #[serde(alias = "rankImg", default, deserialize_with = "lenient::string")]
     pub rank_img: String,
-    #[serde(alias = "rankedSeason")]
+    #[serde(alias = "rankedSeason", default, deserialize_with = "lenient::string")]
     pub ranked_season: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct ApexBans {
-    #[serde(alias = "isActive")]
+    #[serde(alias = "isActive", default, deserialize_with = "lenient::boolean")]
     pub is_active: bool,
-    #[serde(alias = "remainingSeconds")]
+    #[serde(alias = "remainingSeconds", default, deserialize_with = "lenient::int")]
     pub remaining_seconds: i32,
-    #[serde(alias = "last_banReason")]
+    #[serde(
+        alias = "last_banReason",
+        default,
+        deserialize_with = "lenient::string"
+    )]
     pub last_ban_reason: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ApexGame {
+    #[serde(deserialize_with = "lenient::string")]
     pub uid: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub name: String,
-    #[serde(alias = "legendPlayed")]
+    #[serde(alias = "legendPlayed", default, deserialize_with = "lenient::string")]
     pub legend_played: String,
-    #[serde(alias = "gameMode")]
+    #[serde(alias = "gameMode", default, deserialize_with = "lenient::string")]
     pub game_mode: String,
-    #[serde(alias = "gameLengthSecs")]
+    #[serde(alias = "gameLengthSecs", default, deserialize_with = "lenient::int")]
     pub game_length_seconds: i32,
-    #[serde(alias = "gameEndTimestamp")]
+    #[serde(alias = "gameEndTimestamp", deserialize_with = "lenient::int")]
     pub game_end_timestamp: i32,
-    #[serde(alias = "gameData")]
+    #[serde(alias = "gameData", default, deserialize_with = "lenient::items")]
     pub game_data: Vec<ApexGameData>,
-    #[serde(alias = "estimatedLevelProgress")]
+    #[serde(
+        alias = "estimatedLevelProgress",
+        default,
+        deserialize_with = "lenient::int"
+    )]
     pub estimated_level_progress: i32,
-    #[serde(alias = "BRScoreChange")]
+    #[serde(alias = "BRScoreChange", default, deserialize_with = "lenient::int")]
     pub br_score_change: i32,
-    #[serde(alias = "BRScore")]
+    #[serde(alias = "BRScore", default, deserialize_with = "lenient::int")]
     pub br_score: i32,
-    #[serde(alias = "ArenasScoreChange")]
+    #[serde(
+        alias = "ArenasScoreChange",
+        default,
+        deserialize_with = "lenient::int"
+    )]
     pub arenas_score_change: i32,
-    #[serde(alias = "ArenasScore")]
+    #[serde(alias = "ArenasScore", default, deserialize_with = "lenient::int")]
     pub arenas_score: i32,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub cosmetics: ApexCosmetics,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ApexCosmetics {
+    #[serde(default, deserialize_with = "lenient::string")]
     pub pose: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub skin: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub frame: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub intro: String,
-    #[serde(alias = "poseRarity")]
+    #[serde(alias = "poseRarity", default, deserialize_with = "lenient::string")]
     pub pose_rarity: String,
-    #[serde(alias = "skinRarity")]
+    #[serde(alias = "skinRarity", default, deserialize_with = "lenient::string")]
     pub skin_rarity: String,
-    #[serde(alias = "frameRarity")]
+    #[serde(alias = "frameRarity", default, deserialize_with = "lenient::string")]
     pub frame_rarity: String,
-    #[serde(alias = "introRarity")]
+    #[serde(alias = "introRarity", default, deserialize_with = "lenient::string")]
     pub intro_rarity: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ApexGameData {
+    #[serde(deserialize_with = "lenient::string")]
     pub key: String,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub value: i32,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub name: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ApexProfile {
+    #[serde(deserialize_with = "lenient::string")]
     pub name: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub uid: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub pid: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub avatar: String,
 }
 
-#[derive(Deserialize, Debug)]
+/// Platform a player account belongs to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    #[serde(rename = "PC")]
+    Pc,
+    #[serde(rename = "PS4")]
+    Playstation,
+    #[serde(rename = "X1")]
+    Xbox,
+    #[serde(rename = "SWITCH")]
+    Switch,
+}
+
+impl Platform {
+    /// Every platform supported by the API
+    pub const ALL: [Platform; 4] = [
+        Platform::Pc,
+        Platform::Playstation,
+        Platform::Xbox,
+        Platform::Switch,
+    ];
+
+    /// The name of the platform as used by the API (`PC`, `PS4`, `X1` or `SWITCH`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Pc => "PC",
+            Platform::Playstation => "PS4",
+            Platform::Xbox => "X1",
+            Platform::Switch => "SWITCH",
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Platform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Platform::ALL
+            .iter()
+            .find(|p| p.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown platform: {}", s))
+    }
+}
+
+/// A profile found by the name to UID API, together with the platform it was found on
+#[derive(Serialize, Debug)]
+pub struct PlatformProfile {
+    pub platform: Platform,
+    pub profile: ApexProfile,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ApexMapRotation {
     pub battle_royale: ApexMapRotationData,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub arenas: ApexMapRotationData,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub ranked: ApexRankedMapRotationData,
-    #[serde(alias = "arenasRanked")]
+    #[serde(
+        alias = "arenasRanked",
+        default,
+        deserialize_with = "lenient::or_default"
+    )]
     pub arenas_ranked: ApexMapRotationData,
 }
 
-#[derive(Deserialize, Debug)]
+impl ApexMapRotation {
+    /// Unix timestamp of the next time the current map of any mode changes.
+    /// The modes missing from the response are left out.
+    pub fn next_change(&self) -> i64 {
+        [&self.battle_royale, &self.arenas, &self.arenas_ranked]
+            .iter()
+            .map(|mode| mode.current.end)
+            .filter(|end| *end > 0)
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ApexMapRotationData {
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub current: ApexMapRotationItem,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub next: ApexMapRotationItem,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ApexRankedMapRotationData {
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub current: ApexRankedMapRotationItem,
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub next: ApexRankedMapRotationItem,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ApexRankedMapRotationItem {
+    #[serde(default, deserialize_with = "lenient::string")]
     pub map: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ApexMapRotationItem {
+    #[serde(default, deserialize_with = "lenient::int")]
     pub start: i64,
+    #[serde(default, deserialize_with = "lenient::int")]
     pub end: i64,
-    #[serde(alias = "readableDate_start")]
+    #[serde(
+        alias = "readableDate_start",
+        default,
+        deserialize_with = "lenient::string"
+    )]
     pub readable_date_start: String,
-    #[serde(alias = "readableDate_end")]
+    #[serde(
+        alias = "readableDate_end",
+        default,
+        deserialize_with = "lenient::string"
+    )]
     pub readable_date_end: String,
+    #[serde(default, deserialize_with = "lenient::string")]
     pub map: String,
-    #[serde(alias = "DurationInSecs")]
+    #[serde(alias = "DurationInSecs", default, deserialize_with = "lenient::int")]
     pub duration_in_seconds: i32,
-    #[serde(alias = "DurationInMinutes")]
+    #[serde(
+        alias = "DurationInMinutes",
+        default,
+        deserialize_with = "lenient::int"
+    )]
     pub duration_in_minutes: i32,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Debug)]
 pub struct Stat<V> {
     pub name: String,
     pub value: V,
 }
 
+// Both fields of a stat are parsed leniently, the value with any type that can be deserialized
+impl<'de, V: DeserializeOwned> Deserialize<'de> for Stat<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawStat {
+            #[serde(default, deserialize_with = "lenient::string")]
+            name: String,
+            #[serde(default)]
+            value: Value,
+        }
+
+        let stat = RawStat::deserialize(deserializer)?;
+        let value = lenient::any(stat.value)
+            .ok_or_else(|| D::Error::custom("unexpected value for the stat"))?;
+
+        Ok(Self {
+            name: stat.name,
+            value,
+        })
+    }
+}
+
 impl Default for Stat<i32> {
     fn default() -> Self {
-        Self { name: "Missing attribute".to_string(), value: 0 }
+        Self {
+            name: "Missing attribute".to_string(),
+            value: 0,
+        }
     }
 }
 
 impl Default for Stat<String> {
     fn default() -> Self {
-        Self { name: "Missing attribute".to_string(), value: "None".to_string() }
+        Self {
+            name: "Missing attribute".to_string(),
+            value: "None".to_string(),
+        }
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ApexStats {
-    #[serde(alias = "kills", default)]
+    #[serde(alias = "kills", default, deserialize_with = "lenient::or_default")]
     pub br_kills: Stat<i32>,
-    #[serde(alias = "damage", default)]
+    #[serde(alias = "damage", default, deserialize_with = "lenient::or_default")]
     pub br_damage: Stat<i32>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub arenas_damage: Stat<i32>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub games_played: Stat<i32>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient::or_default")]
     pub kd: Stat<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ApexError {
     #[serde(alias = "Error")]
     pub message: String,
 }
+
+/// Region reported by the server status API
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Region {
+    EuWest,
+    EuEast,
+    UsWest,
+    UsCentral,
+    UsEast,
+    SouthAmerica,
+    Asia,
+    /// A region that is not known yet, with its name as sent by the API
+    Unknown(String),
+}
+
+impl Region {
+    /// Every known region
+    pub const ALL: [Region; 7] = [
+        Region::EuWest,
+        Region::EuEast,
+        Region::UsWest,
+        Region::UsCentral,
+        Region::UsEast,
+        Region::SouthAmerica,
+        Region::Asia,
+    ];
+
+    /// The name of the region as used by the API, e.g. `EU-West`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Region::EuWest => "EU-West",
+            Region::EuEast => "EU-East",
+            Region::UsWest => "US-West",
+            Region::UsCentral => "US-Central",
+            Region::UsEast => "US-East",
+            Region::SouthAmerica => "SouthAmerica",
+            Region::Asia => "Asia",
+            Region::Unknown(name) => name,
+        }
+    }
+}
+
+impl From<String> for Region {
+    fn from(name: String) -> Self {
+        Region::ALL
+            .iter()
+            .find(|region| region.as_str() == name)
+            .cloned()
+            .unwrap_or(Region::Unknown(name))
+    }
+}
+
+impl From<Region> for String {
+    fn from(region: Region) -> Self {
+        match region {
+            Region::Unknown(name) => name,
+            region => region.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Health of a service in a given region
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceHealth {
+    #[serde(rename = "UP")]
+    Up,
+    #[serde(rename = "SLOW")]
+    Slow,
+    #[serde(rename = "DOWN")]
+    Down,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RegionStatus {
+    #[serde(alias = "Status")]
+    pub status: ServiceHealth,
+    #[serde(alias = "HTTPCode")]
+    pub http_code: i32,
+    #[serde(alias = "ResponseTime")]
+    pub response_time: i32,
+    #[serde(alias = "QueryTimestamp")]
+    pub query_timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ServerStatus {
+    #[serde(alias = "Origin_login")]
+    pub origin_login: HashMap<Region, RegionStatus>,
+    /// The Apex Legends game servers
+    #[serde(alias = "EA_novafusion")]
+    pub ea_novafusion: HashMap<Region, RegionStatus>,
+    #[serde(alias = "EA_accounts")]
+    pub ea_accounts: HashMap<Region, RegionStatus>,
+    #[serde(alias = "ApexOauth_Crossplay")]
+    pub crossplay_auth: HashMap<Region, RegionStatus>,
+}
+
+impl ServerStatus {
+    /// Returns every service as a pair of (service name, status per region)
+    pub fn services(&self) -> [(&'static str, &HashMap<Region, RegionStatus>); 4] {
+        [
+            ("Origin login", &self.origin_login),
+            ("Apex Legends servers", &self.ea_novafusion),
+            ("EA accounts", &self.ea_accounts),
+            ("Crossplay auth", &self.crossplay_auth),
+        ]
+    }
+
+    /// Returns the names of the services that are down in the given region
+    pub fn down_services(&self, region: &Region) -> Vec<&'static str> {
+        self.services()
+            .iter()
+            .filter(|(_, regions)| {
+                matches!(regions.get(region), Some(r) if r.status == ServiceHealth::Down)
+            })
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    /// Whether any service is down in the given region
+    pub fn is_anything_down(&self, region: &Region) -> bool {
+        !self.down_services(region).is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PredatorThresholds {
+    #[serde(alias = "RP")]
+    pub battle_royale: PredatorPlatforms,
+    #[serde(alias = "AP")]
+    pub arenas: PredatorPlatforms,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PredatorPlatforms {
+    #[serde(alias = "PC")]
+    pub pc: PredatorThreshold,
+    #[serde(alias = "PS4")]
+    pub playstation: PredatorThreshold,
+    #[serde(alias = "X1")]
+    pub xbox: PredatorThreshold,
+    #[serde(alias = "SWITCH")]
+    pub switch: PredatorThreshold,
+}
+
+impl PredatorPlatforms {
+    /// Returns the threshold for a platform.
+    /// The platform of a player, `ApexGlobal::platform`, can be parsed into a [`Platform`]
+    pub fn get(&self, platform: Platform) -> &PredatorThreshold {
+        match platform {
+            Platform::Pc => &self.pc,
+            Platform::Playstation => &self.playstation,
+            Platform::Xbox => &self.xbox,
+            Platform::Switch => &self.switch,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PredatorThreshold {
+    /// Leaderboard position of the last Apex Predator
+    #[serde(alias = "foundRank")]
+    pub found_rank: i32,
+    /// Points needed to reach Apex Predator
+    #[serde(alias = "val")]
+    pub value: i32,
+    /// UID of the last Apex Predator
+    pub uid: String,
+    #[serde(alias = "updateTimestamp")]
+    pub update_timestamp: i64,
+    /// Number of Masters and Apex Predators
+    #[serde(alias = "totalMastersAndPreds")]
+    pub total_masters_and_preds: i32,
+}
+
+impl PredatorThreshold {
+    /// Points a player with the given `rank_score` still needs to reach Apex Predator
+    pub fn points_to_predator(&self, rank_score: i32) -> i32 {
+        (self.value - rank_score).max(0)
+    }
+}
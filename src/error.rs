@@ -0,0 +1,91 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+
+use crate::player_name::PlayerNameError;
+
+/// Errors returned by [`crate::ApexClient`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The player name was rejected before sending any request
+    InvalidPlayerName(PlayerNameError),
+    /// The API key contains characters that cannot be sent in a header
+    InvalidApiKey,
+    /// The API answered with an error status code.
+    /// See [https://apexlegendsapi.com/#errors](https://apexlegendsapi.com/#errors)
+    Status(StatusCode),
+    /// The API answered 200 OK with an error message, e.g. the player was not found
+    Api(String),
+    /// The response does not have the expected shape
+    Parse(String),
+    /// The request could not be sent
+    Request(String),
+    /// The API took too long to answer
+    Timeout,
+    /// The connection failed while reading the body of the response
+    Body(String),
+    /// The body of the response is not valid UTF-8
+    InvalidUtf8,
+    /// The body of the response is bigger than the maximum size, in bytes, set in the client
+    ResponseTooLarge(usize),
+    /// A cassette could not be read or written, or has no response for a request.
+    /// See [`crate::vcr`]
+    Cassette(String),
+}
+
+impl Error {
+    /// Whether sending the same request again later may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Timeout | Error::Request(_) | Error::Body(_) => true,
+            Error::Status(status) => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidPlayerName(e) => write!(f, "Invalid player name: {}", e),
+            Error::InvalidApiKey => f.write_str("The API key contains invalid characters"),
+            Error::Status(StatusCode::TOO_MANY_REQUESTS) => {
+                f.write_str("Too many requests: wait 1 or 2 seconds please")
+            }
+            Error::Status(StatusCode::UNAUTHORIZED) => {
+                f.write_str("The API key is incorrect, please contact the bot administrator")
+            }
+            Error::Status(StatusCode::NOT_FOUND) => {
+                f.write_str("Either apexlegendsapi.com is not available or the user does not exist")
+            }
+            Error::Status(StatusCode::INTERNAL_SERVER_ERROR) => {
+                f.write_str("There was an internal server error")
+            }
+            Error::Status(status) => write!(f, "{}", status),
+            Error::Api(message) => f.write_str(message),
+            Error::Parse(message) => write!(f, "Unable to deserialize JSON: {}", message),
+            Error::Timeout => f.write_str("The request timed out"),
+            Error::Body(message) => {
+                write!(f, "There was an error reading the response: {}", message)
+            }
+            Error::InvalidUtf8 => f.write_str("The response is not valid UTF-8"),
+            Error::ResponseTooLarge(max_size) => {
+                write!(f, "The response is bigger than {} bytes", max_size)
+            }
+            Error::Request(message) => {
+                write!(f, "There was an error sending the request: {}", message)
+            }
+            Error::Cassette(message) => write!(f, "Cassette error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<PlayerNameError> for Error {
+    fn from(e: PlayerNameError) -> Self {
+        Error::InvalidPlayerName(e)
+    }
+}
@@ -0,0 +1,409 @@
+//! Realistic values for every data type, to test code that uses them without calling the API.
+//!
+//! Requires the `test-util` feature. Each type has a `fake()` constructor,
+//! override the fields you need with the struct update syntax:
+//!
+//! ```
+//! use apex_legends_api::data_types::{ApexGlobal, ApexRank, ApexUser};
+//!
+//! let user = ApexUser {
+//!     global: ApexGlobal {
+//!         level: 42,
+//!         rank: ApexRank {
+//!             rank_score: 12000,
+//!             ..ApexRank::fake()
+//!         },
+//!         ..ApexGlobal::fake()
+//!     },
+//!     ..ApexUser::fake()
+//! };
+//!
+//! assert_eq!(user.global.name, "HeyImLifeline");
+//! assert_eq!(user.global.level, 42);
+//! ```
+//!
+//! The values match the ones the API returns for a PC player on November 22nd 2021.
+
+use std::collections::HashMap;
+
+use crate::{data_types::*, date::readable_date};
+
+impl ApexUser {
+    /// A Diamond 2 PC player, online in the lobby
+    pub fn fake() -> Self {
+        Self {
+            global: ApexGlobal::fake(),
+            realtime: ApexRealtime::fake(),
+            stats: ApexStats::fake(),
+        }
+    }
+}
+
+impl ApexGlobal {
+    pub fn fake() -> Self {
+        Self {
+            name: "HeyImLifeline".to_string(),
+            uid: 1000575311440,
+            avatar:
+                "https://secure.download.dm.origin.com/production/avatar/prod/1/599/208x208.JPEG"
+                    .to_string(),
+            platform: "PC".to_string(),
+            level: 512,
+            level_prestige: 0,
+            to_next_level_percent: 37,
+            rank: ApexRank::fake(),
+            arena: ApexRank {
+                rank_score: 4321,
+                rank_name: "Platinum".to_string(),
+                rank_division: 1,
+                rank_img: "https://api.mozambiquehe.re/assets/ranks/platinum1.png".to_string(),
+                ..ApexRank::fake()
+            },
+            bans: ApexBans::fake(),
+            battlepass: ApexBattlepass::fake(),
+        }
+    }
+}
+
+impl ApexRealtime {
+    /// Online in the lobby, in an open party
+    pub fn fake() -> Self {
+        Self {
+            lobby_state: "open".to_string(),
+            is_online: 1,
+            is_in_game: 0,
+            can_join: 1,
+            party_full: 0,
+            selected_legend: "Lifeline".to_string(),
+            current_state: "inLobby".to_string(),
+        }
+    }
+}
+
+impl ApexBattlepass {
+    pub fn fake() -> Self {
+        Self {
+            level: "78".to_string(),
+            history: ApexBattlepassHistory::fake(),
+        }
+    }
+}
+
+impl ApexBattlepassHistory {
+    pub fn fake() -> Self {
+        Self {
+            season1: 110,
+            season2: 110,
+            season3: 87,
+            season4: 110,
+            season5: 110,
+            season6: 64,
+            season7: 110,
+            season8: 110,
+            season9: 110,
+            season10: 110,
+        }
+    }
+}
+
+impl ApexRank {
+    /// Diamond 2, in Battle Royale
+    pub fn fake() -> Self {
+        Self {
+            rank_score: 10842,
+            rank_name: "Diamond".to_string(),
+            rank_division: 2,
+            rank_img: "https://api.mozambiquehe.re/assets/ranks/diamond2.png".to_string(),
+            ranked_season: "season11_split_1".to_string(),
+        }
+    }
+}
+
+impl ApexBans {
+    /// No active ban
+    pub fn fake() -> Self {
+        Self {
+            is_active: false,
+            remaining_seconds: 0,
+            last_ban_reason: "NONE".to_string(),
+        }
+    }
+}
+
+impl ApexStats {
+    pub fn fake() -> Self {
+        Self {
+            br_kills: Stat {
+                name: "BR Kills".to_string(),
+                value: 12345,
+            },
+            br_damage: Stat {
+                name: "BR Damage".to_string(),
+                value: 3456789,
+            },
+            arenas_damage: Stat {
+                name: "Arenas Damage".to_string(),
+                value: 402113,
+            },
+            games_played: Stat {
+                name: "Games played".to_string(),
+                value: 2001,
+            },
+            kd: Stat {
+                name: "KD".to_string(),
+                value: "-1".to_string(),
+            },
+        }
+    }
+}
+
+impl ApexGame {
+    /// A Battle Royale game with 4 kills
+    pub fn fake() -> Self {
+        Self {
+            uid: "1000575311440".to_string(),
+            name: "HeyImLifeline".to_string(),
+            legend_played: "Lifeline".to_string(),
+            game_mode: "BATTLE_ROYALE".to_string(),
+            game_length_seconds: 1104,
+            game_end_timestamp: 1637597100,
+            game_data: vec![
+                ApexGameData::fake(),
+                ApexGameData {
+                    key: "specialEvent_damage".to_string(),
+                    value: 1203,
+                    name: Some("BR Damage".to_string()),
+                },
+            ],
+            estimated_level_progress: 12,
+            br_score_change: 87,
+            br_score: 10842,
+            arenas_score_change: 0,
+            arenas_score: 4321,
+            cosmetics: ApexCosmetics::fake(),
+        }
+    }
+}
+
+impl ApexCosmetics {
+    pub fn fake() -> Self {
+        Self {
+            pose: "Default".to_string(),
+            skin: "Combat Medic".to_string(),
+            frame: "Default".to_string(),
+            intro: "Default".to_string(),
+            pose_rarity: "Common".to_string(),
+            skin_rarity: "Epic".to_string(),
+            frame_rarity: "Common".to_string(),
+            intro_rarity: "Common".to_string(),
+        }
+    }
+}
+
+impl ApexGameData {
+    /// The kills of a game
+    pub fn fake() -> Self {
+        Self {
+            key: "specialEvent_kills".to_string(),
+            value: 4,
+            name: Some("BR Kills".to_string()),
+        }
+    }
+}
+
+impl ApexProfile {
+    pub fn fake() -> Self {
+        Self {
+            name: "HeyImLifeline".to_string(),
+            uid: "1000575311440".to_string(),
+            pid: "1000575311440".to_string(),
+            avatar:
+                "https://secure.download.dm.origin.com/production/avatar/prod/1/599/208x208.JPEG"
+                    .to_string(),
+        }
+    }
+}
+
+impl PlatformProfile {
+    pub fn fake() -> Self {
+        Self {
+            platform: Platform::Pc,
+            profile: ApexProfile::fake(),
+        }
+    }
+}
+
+impl ApexMapRotation {
+    /// Olympus then Storm Point in Battle Royale, Phase Runner then Party Crasher in Arenas
+    pub fn fake() -> Self {
+        Self {
+            battle_royale: ApexMapRotationData::fake(),
+            arenas: ApexMapRotationData {
+                current: ApexMapRotationItem::fake_at(1637597400, 900, "Phase Runner"),
+                next: ApexMapRotationItem::fake_at(1637598300, 900, "Party Crasher"),
+            },
+            ranked: ApexRankedMapRotationData::fake(),
+            arenas_ranked: ApexMapRotationData {
+                current: ApexMapRotationItem::fake_at(1637597400, 900, "Overflow"),
+                next: ApexMapRotationItem::fake_at(1637598300, 900, "Drop Off"),
+            },
+        }
+    }
+}
+
+impl ApexMapRotationData {
+    /// Olympus then Storm Point, 90 minutes each
+    pub fn fake() -> Self {
+        Self {
+            current: ApexMapRotationItem::fake(),
+            next: ApexMapRotationItem::fake_at(1637602200, 5400, "Storm Point"),
+        }
+    }
+}
+
+impl ApexRankedMapRotationData {
+    pub fn fake() -> Self {
+        Self {
+            current: ApexRankedMapRotationItem::fake(),
+            next: ApexRankedMapRotationItem {
+                map: "Unknown".to_string(),
+            },
+        }
+    }
+}
+
+impl ApexRankedMapRotationItem {
+    pub fn fake() -> Self {
+        Self {
+            map: "Storm Point".to_string(),
+        }
+    }
+}
+
+impl ApexMapRotationItem {
+    /// Olympus for 90 minutes
+    pub fn fake() -> Self {
+        Self::fake_at(1637596800, 5400, "Olympus")
+    }
+
+    /// `map` from the `start` Unix timestamp for `duration` seconds
+    pub fn fake_at(start: i64, duration: i32, map: &str) -> Self {
+        let end = start + duration as i64;
+
+        Self {
+            start,
+            end,
+            readable_date_start: readable_date(start),
+            readable_date_end: readable_date(end),
+            map: map.to_string(),
+            duration_in_seconds: duration,
+            duration_in_minutes: duration / 60,
+        }
+    }
+}
+
+impl ApexError {
+    /// The error of an unknown player
+    pub fn fake() -> Self {
+        Self {
+            message: "Player HeyImNobody not found".to_string(),
+        }
+    }
+}
+
+impl RegionStatus {
+    pub fn fake() -> Self {
+        Self {
+            status: ServiceHealth::Up,
+            http_code: 200,
+            response_time: 72,
+            query_timestamp: 1637597652,
+        }
+    }
+}
+
+impl ServerStatus {
+    /// Every service up in every region
+    pub fn fake() -> Self {
+        let regions = || -> HashMap<Region, RegionStatus> {
+            Region::ALL
+                .iter()
+                .map(|region| (region.clone(), RegionStatus::fake()))
+                .collect()
+        };
+
+        Self {
+            origin_login: regions(),
+            ea_novafusion: regions(),
+            ea_accounts: regions(),
+            crossplay_auth: regions(),
+        }
+    }
+}
+
+impl PredatorThresholds {
+    pub fn fake() -> Self {
+        Self {
+            battle_royale: PredatorPlatforms::fake(),
+            arenas: PredatorPlatforms {
+                pc: PredatorThreshold {
+                    value: 9902,
+                    total_masters_and_preds: 5120,
+                    ..PredatorThreshold::fake()
+                },
+                playstation: PredatorThreshold {
+                    value: 9430,
+                    total_masters_and_preds: 3322,
+                    ..PredatorThreshold::fake()
+                },
+                xbox: PredatorThreshold {
+                    value: 8011,
+                    total_masters_and_preds: 987,
+                    ..PredatorThreshold::fake()
+                },
+                switch: PredatorThreshold {
+                    value: 6700,
+                    total_masters_and_preds: 150,
+                    ..PredatorThreshold::fake()
+                },
+            },
+        }
+    }
+}
+
+impl PredatorPlatforms {
+    /// The Battle Royale thresholds
+    pub fn fake() -> Self {
+        Self {
+            pc: PredatorThreshold::fake(),
+            playstation: PredatorThreshold {
+                value: 14210,
+                total_masters_and_preds: 6012,
+                ..PredatorThreshold::fake()
+            },
+            xbox: PredatorThreshold {
+                value: 12033,
+                total_masters_and_preds: 1873,
+                ..PredatorThreshold::fake()
+            },
+            switch: PredatorThreshold {
+                value: 9820,
+                total_masters_and_preds: 402,
+                ..PredatorThreshold::fake()
+            },
+        }
+    }
+}
+
+impl PredatorThreshold {
+    /// Battle Royale on PC
+    pub fn fake() -> Self {
+        Self {
+            found_rank: 750,
+            value: 15108,
+            uid: "1009563270711".to_string(),
+            update_timestamp: 1637597180,
+            total_masters_and_preds: 8734,
+        }
+    }
+}
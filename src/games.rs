@@ -0,0 +1,271 @@
+use std::{cmp::Reverse, collections::VecDeque};
+
+use crate::data_types::ApexGame;
+
+cfg_runtime! {
+    use futures::stream::{self, Stream, StreamExt};
+
+    use crate::{transport::Transport, ApexClient, Error};
+}
+
+/// Game mode with ranked points, also used to filter the match history API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    BattleRoyale,
+    Arenas,
+}
+
+impl GameMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            GameMode::BattleRoyale => "BATTLE_ROYALE",
+            GameMode::Arenas => "ARENAS",
+        }
+    }
+}
+
+/// Options for the match history API.
+/// See [https://apexlegendsapi.com/#match-history](https://apexlegendsapi.com/#match-history)
+///
+/// # Examples
+/// ```
+/// use apex_legends_api::games::{GameMode, GamesQuery};
+///
+/// let query = GamesQuery::new("1000575311440")
+///     .mode(GameMode::BattleRoyale)
+///     .limit(20);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GamesQuery {
+    uid: String,
+    mode: Option<GameMode>,
+    start: Option<i64>,
+    end: Option<i64>,
+    limit: Option<u32>,
+    add_to_tracking: bool,
+}
+
+impl GamesQuery {
+    /// Creates a query for every game of the player with the given UID
+    pub fn new<S: Into<String>>(uid: S) -> Self {
+        Self {
+            uid: uid.into(),
+            mode: None,
+            start: None,
+            end: None,
+            limit: None,
+            add_to_tracking: false,
+        }
+    }
+
+    /// Only return games of the given mode
+    pub fn mode(mut self, mode: GameMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Only return games that ended at or after this Unix timestamp
+    pub fn start(mut self, timestamp: i64) -> Self {
+        self.start = Some(timestamp);
+        self
+    }
+
+    /// Only return games that ended at or before this Unix timestamp
+    pub fn end(mut self, timestamp: i64) -> Self {
+        self.end = Some(timestamp);
+        self
+    }
+
+    /// Maximum number of games to return
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Also add the player to the tracked players of this API key
+    pub fn add_to_tracking(mut self, add: bool) -> Self {
+        self.add_to_tracking = add;
+        self
+    }
+
+    pub(crate) fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("uid", self.uid.clone())];
+
+        if let Some(mode) = self.mode {
+            params.push(("mode", mode.as_str().to_string()));
+        }
+        if let Some(start) = self.start {
+            params.push(("start", start.to_string()));
+        }
+        if let Some(end) = self.end {
+            params.push(("end", end.to_string()));
+        }
+        if let Some(limit) = self.limit {
+            params.push(("limit", limit.to_string()));
+        }
+        if self.add_to_tracking {
+            params.push(("action", "add".to_string()));
+        }
+
+        params
+    }
+}
+
+/// What to do next while walking the match history
+pub(crate) enum HistoryStep {
+    Game(Box<ApexGame>),
+    /// Fetch the next page and hand it to [`HistoryPages::push_page`]
+    Fetch(GamesQuery),
+    Done,
+}
+
+/// What tells two games of a player apart, the API does not send an id
+type GameKey = (i32, String, String, i32);
+
+fn game_key(game: &ApexGame) -> GameKey {
+    (
+        game.game_end_timestamp,
+        game.legend_played.clone(),
+        game.game_mode.clone(),
+        game.game_length_seconds,
+    )
+}
+
+/// The state of a walk through the match history, shared by the async and blocking clients
+pub(crate) struct HistoryPages {
+    query: GamesQuery,
+    until: i64,
+    buffer: VecDeque<ApexGame>,
+    /// The games already returned that ended at the `end` of the next page, which returns them again
+    boundary: Vec<GameKey>,
+    done: bool,
+}
+
+impl HistoryPages {
+    pub(crate) fn new(query: GamesQuery, until: i64) -> Self {
+        Self {
+            query,
+            until,
+            buffer: VecDeque::new(),
+            boundary: Vec::new(),
+            done: false,
+        }
+    }
+
+    pub(crate) fn next_step(&mut self) -> HistoryStep {
+        if let Some(game) = self.buffer.pop_front() {
+            HistoryStep::Game(Box::new(game))
+        } else if self.done {
+            HistoryStep::Done
+        } else {
+            HistoryStep::Fetch(self.query.clone())
+        }
+    }
+
+    /// Stops the walk, e.g. after an error
+    pub(crate) fn stop(&mut self) {
+        self.done = true;
+    }
+
+    pub(crate) fn push_page(&mut self, mut page: Vec<ApexGame>) {
+        let full =
+            matches!(self.query.limit, Some(limit) if limit > 0 && page.len() >= limit as usize);
+        // Several games can end at the same time, so each page starts at the oldest game of the previous one
+        page.retain(|g| !self.boundary.contains(&game_key(g)));
+
+        match page.iter().map(|g| g.game_end_timestamp as i64).min() {
+            Some(oldest) => {
+                self.done = !full || oldest < self.until;
+
+                if self.query.end != Some(oldest) {
+                    self.boundary.clear();
+                }
+                self.boundary.extend(
+                    page.iter()
+                        .filter(|g| g.game_end_timestamp as i64 == oldest)
+                        .map(game_key),
+                );
+                self.query.end = Some(oldest);
+            }
+            // A full page of games already returned, all ending at `end`: the others of that second cannot be reached
+            None => {
+                self.done = !full || self.query.end.is_none();
+                self.query.end = self.query.end.map(|end| end - 1);
+                self.boundary.clear();
+            }
+        }
+
+        page.sort_by_key(|g| Reverse(g.game_end_timestamp));
+        let until = self.until;
+        self.buffer.extend(
+            page.into_iter()
+                .filter(|g| g.game_end_timestamp as i64 >= until),
+        );
+    }
+}
+
+cfg_runtime! {
+    /// Walks a player's match history back to the `until` Unix timestamp, one page of `limit` games at a time.
+    /// Games are yielded newest first. The stream ends after the first error.
+    ///
+    /// If the query has no `limit`, the whole history is fetched in a single request.
+    ///
+    /// # Parameters
+    /// * `query` - The query for the first page
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `until` - Unix timestamp of the oldest game to return
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    pub fn history_retry(
+        query: GamesQuery,
+        api_key: &str,
+        until: i64,
+        retry: bool,
+    ) -> impl Stream<Item = Result<ApexGame, String>> {
+        match crate::client(api_key, retry) {
+            Ok(client) => history_stream(client, query, until)
+                .map(|r| r.map_err(|e| e.to_string()))
+                .left_stream(),
+            Err(e) => stream::once(async move { Err(e) }).right_stream(),
+        }
+    }
+
+    /// Walks a player's match history back to the `until` Unix timestamp, one page of `limit` games at a time.
+    /// Games are yielded newest first. The stream ends after the first error.
+    /// This version does not handle code 429 (too many requests), see [`history_retry`]
+    ///
+    /// # Parameters
+    /// * `query` - The query for the first page
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `until` - Unix timestamp of the oldest game to return
+    pub fn history(
+        query: GamesQuery,
+        api_key: &str,
+        until: i64,
+    ) -> impl Stream<Item = Result<ApexGame, String>> {
+        history_retry(query, api_key, until, false)
+    }
+
+    pub(crate) fn history_stream<T: Transport>(
+        client: ApexClient<T>,
+        query: GamesQuery,
+        until: i64,
+    ) -> impl Stream<Item = Result<ApexGame, Error>> {
+        let pages = HistoryPages::new(query, until);
+
+        stream::unfold((client, pages), |(client, mut pages)| async move {
+            loop {
+                match pages.next_step() {
+                    HistoryStep::Game(game) => return Some((Ok(*game), (client, pages))),
+                    HistoryStep::Done => return None,
+                    HistoryStep::Fetch(query) => match client.get_games(&query).await {
+                        Ok(page) => pages.push_page(page),
+                        Err(e) => {
+                            pages.stop();
+                            return Some((Err(e), (client, pages)));
+                        }
+                    },
+                }
+            }
+        })
+    }
+}
@@ -1,28 +1,165 @@
-use reqwest::{self, header::HeaderMap};
+use std::time::Duration;
 
-pub async fn get_request(url: String) -> Result<String, (reqwest::Error, Option<HeaderMap>)> {
-    let response = reqwest::get(url).await;
+use crate::{
+    transport::{Request, Response},
+    Error,
+};
 
-    match response {
-        Ok(res) => {
-            let headers = res.headers().clone();
+cfg_runtime! {
+    use std::future::Future;
 
-            match res.error_for_status() {
-                Ok(r) => Ok(r
-                    .text()
-                    .await
-                    .expect("Could not retrieve text from the successful request")),
-                Err(e) => Err((e, Some(headers))), //Err((, Some(headers))),
+    use crate::rt;
+}
+
+fn request_error(e: reqwest::Error) -> Error {
+    if e.is_timeout() {
+        Error::Timeout
+    } else {
+        Error::Request(e.to_string())
+    }
+}
+
+/// Fails early if the `Content-Length` of a response is already over `max_size`
+fn check_length(max_size: Option<usize>, length: Option<u64>) -> Result<(), Error> {
+    match (max_size, length) {
+        (Some(max_size), Some(length)) if length > max_size as u64 => {
+            Err(Error::ResponseTooLarge(max_size))
+        }
+        _ => Ok(()),
+    }
+}
+
+cfg_runtime! {
+    /// Fails with `Error::Timeout` if `future` does not complete within `timeout`
+    async fn with_timeout<F: Future>(timeout: Option<Duration>, future: F) -> Result<F::Output, Error> {
+        match timeout {
+            Some(timeout) => rt::timeout(timeout, future).await.ok_or(Error::Timeout),
+            None => Ok(future.await),
+        }
+    }
+
+    /// Reads the body of a response, one chunk at a time so no more than `max_size` bytes are ever kept
+    async fn read_body(
+        mut res: reqwest::Response,
+        read_timeout: Option<Duration>,
+        max_size: Option<usize>,
+    ) -> Result<String, Error> {
+        check_length(max_size, res.content_length())?;
+
+        let mut body = Vec::new();
+
+        while let Some(chunk) = with_timeout(read_timeout, res.chunk())
+            .await?
+            .map_err(|e| match request_error(e) {
+                Error::Request(message) => Error::Body(message),
+                e => e,
+            })?
+        {
+            if matches!(max_size, Some(max_size) if body.len() + chunk.len() > max_size) {
+                return Err(Error::ResponseTooLarge(max_size.unwrap_or_default()));
             }
+
+            body.extend_from_slice(&chunk);
         }
-        Err(e) => Err((e, None)),
+
+        String::from_utf8(body).map_err(|_| Error::InvalidUtf8)
+    }
+
+    /// Sends a GET request. `read_timeout` applies separately to receiving the headers and each part of the body.
+    pub async fn get_request(
+        client: &reqwest::Client,
+        request: Request,
+        read_timeout: Option<Duration>,
+        max_size: Option<usize>,
+    ) -> Result<Response, Error> {
+        let res = with_timeout(
+            read_timeout,
+            client.get(&request.url).headers(request.headers).send(),
+        )
+        .await?
+        .map_err(request_error)?;
+
+        let status = res.status();
+        let headers = res.headers().clone();
+
+        let body = if status.is_success() {
+            read_body(res, read_timeout, max_size).await?
+        } else {
+            String::new()
+        };
+
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
     }
 }
 
-#[allow(dead_code)]
-pub async fn post_request(url: &str, body: String) -> Result<String, reqwest::Error> {
-    let client = reqwest::Client::new();
+/// Reads the body of a blocking response, never keeping more than `max_size` bytes
+#[cfg(feature = "blocking")]
+fn read_body_blocking(
+    res: reqwest::blocking::Response,
+    max_size: Option<usize>,
+) -> Result<String, Error> {
+    use std::io::{ErrorKind, Read};
+
+    check_length(max_size, res.content_length())?;
+
+    let limit = max_size.map_or(u64::MAX, |max_size| max_size as u64 + 1);
+    let mut body = Vec::new();
+
+    res.take(limit)
+        .read_to_end(&mut body)
+        .map_err(|e| match e.kind() {
+            ErrorKind::TimedOut => Error::Timeout,
+            _ => Error::Body(e.to_string()),
+        })?;
+
+    if let Some(max_size) = max_size.filter(|max_size| body.len() > *max_size) {
+        return Err(Error::ResponseTooLarge(max_size));
+    }
+
+    String::from_utf8(body).map_err(|_| Error::InvalidUtf8)
+}
+
+/// Sends a GET request and waits for the response. `timeout` applies to the whole request, body included.
+#[cfg(feature = "blocking")]
+pub fn get_request_blocking(
+    client: &reqwest::blocking::Client,
+    request: Request,
+    timeout: Option<Duration>,
+    max_size: Option<usize>,
+) -> Result<Response, Error> {
+    let mut builder = client.get(&request.url).headers(request.headers);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    let res = builder.send().map_err(request_error)?;
+
+    let status = res.status();
+    let headers = res.headers().clone();
 
+    let body = if status.is_success() {
+        read_body_blocking(res, max_size)?
+    } else {
+        String::new()
+    };
+
+    Ok(Response {
+        status,
+        headers,
+        body,
+    })
+}
+
+#[allow(dead_code)]
+pub async fn post_request(
+    client: &reqwest::Client,
+    url: &str,
+    body: String,
+) -> Result<String, reqwest::Error> {
     match client.post(url).body(body).send().await?.text().await {
         Ok(data) => Ok(data),
         Err(e) => Err(e),
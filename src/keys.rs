@@ -0,0 +1,104 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Why no API key can be used right now
+pub(crate) enum NoKey {
+    /// Every key is rate limited, the first one will be usable again after the duration
+    AllBenched(Duration),
+    /// Every key was rejected by the API
+    AllRevoked,
+}
+
+struct ApiKey {
+    key: String,
+    benched_until: Option<Instant>,
+    revoked: bool,
+}
+
+struct Keys {
+    keys: Vec<ApiKey>,
+    /// Index of the key to try first on the next request
+    next: usize,
+}
+
+/// The API keys of a client, used in turns.
+/// Keys that hit the rate limit are benched for a while, keys rejected by the API are never used again.
+pub(crate) struct KeyPool {
+    keys: Mutex<Keys>,
+}
+
+impl KeyPool {
+    pub(crate) fn new(keys: Vec<String>) -> Self {
+        let keys = keys
+            .into_iter()
+            .map(|key| ApiKey {
+                key,
+                benched_until: None,
+                revoked: false,
+            })
+            .collect();
+
+        Self {
+            keys: Mutex::new(Keys { keys, next: 0 }),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.keys
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys
+            .len()
+    }
+
+    /// Returns the id and value of the next usable key, in round-robin order
+    pub(crate) fn next_key(&self) -> Result<(usize, String), NoKey> {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let count = keys.keys.len();
+        let mut wait: Option<Duration> = None;
+
+        for offset in 0..count {
+            let id = (keys.next + offset) % count;
+            let key = &keys.keys[id];
+
+            if key.revoked {
+                continue;
+            }
+            match key.benched_until {
+                Some(until) if until > now => {
+                    let remaining = until - now;
+                    wait = Some(wait.map_or(remaining, |w| w.min(remaining)));
+                }
+                _ => {
+                    let value = key.key.clone();
+                    keys.next = (id + 1) % count;
+
+                    return Ok((id, value));
+                }
+            }
+        }
+
+        Err(wait.map_or(NoKey::AllRevoked, NoKey::AllBenched))
+    }
+
+    /// Stops using a key for `duration`
+    pub(crate) fn bench(&self, id: usize, duration: Duration) {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(key) = keys.keys.get_mut(id) {
+            key.benched_until = Some(Instant::now() + duration);
+        }
+    }
+
+    /// Stops using a key forever
+    pub(crate) fn revoke(&self, id: usize) {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(key) = keys.keys.get_mut(id) {
+            key.revoked = true;
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Deserializers for the fields of the data types that the API does not always send with the same shape.
+//!
+//! They accept the other shapes the API uses for a value (e.g. a number sent as a string),
+//! and fall back to the default value for anything else, so one unexpected field does not fail the whole response.
+//! Use them with `#[serde(default, deserialize_with = "...")]`,
+//! or without `default` for the fields that identify the data, which must still be present.
+
+use std::convert::TryFrom;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::Value;
+
+/// The value if it has the expected shape, the default otherwise
+pub(crate) fn or_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+
+    Ok(T::deserialize(value).unwrap_or_default())
+}
+
+/// The items that have the expected shape, skipping the others
+pub(crate) fn items<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Array(values) => values
+            .into_iter()
+            .filter_map(|value| T::deserialize(value).ok())
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+/// An integer, also from a float, a numeric string or a boolean
+pub(crate) fn int<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i64> + Default,
+{
+    let int = match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => {
+            let s = s.trim();
+            s.parse::<i64>().ok().or_else(|| {
+                s.parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .map(|f| f as i64)
+            })
+        }
+        Value::Bool(b) => Some(b as i64),
+        _ => None,
+    };
+
+    Ok(int.and_then(|i| T::try_from(i).ok()).unwrap_or_default())
+}
+
+/// A string, also from a number or a boolean
+pub(crate) fn string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => String::new(),
+    })
+}
+
+/// A boolean, also from a number (`0` is false) or a string (`"true"` or `"1"`)
+pub(crate) fn boolean<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Bool(b) => b,
+        Value::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+        Value::String(s) => matches!(s.trim(), "true" | "1"),
+        _ => false,
+    })
+}
+
+/// The other shapes the API uses for the same value: a number sent as a string or the other way around,
+/// an integer sent as a float, or a boolean sent as a number or a string
+fn other_shapes(value: &Value) -> Vec<Value> {
+    match value {
+        Value::String(s) => {
+            let s = s.trim();
+            let mut shapes = Vec::new();
+
+            if let Ok(i) = s.parse::<i64>() {
+                shapes.push(Value::from(i));
+            }
+            if let Some(f) = s.parse::<f64>().ok().filter(|f| f.is_finite()) {
+                shapes.push(Value::from(f));
+                shapes.push(Value::from(f as i64));
+            }
+            if let Ok(b) = s.parse::<bool>() {
+                shapes.push(Value::Bool(b));
+            }
+
+            shapes
+        }
+        Value::Number(n) => {
+            let mut shapes = vec![Value::String(n.to_string())];
+
+            if let (None, Some(f)) = (n.as_i64(), n.as_f64()) {
+                shapes.push(Value::from(f as i64));
+            }
+
+            shapes
+        }
+        Value::Bool(b) => vec![Value::from(*b as i64), Value::String(b.to_string())],
+        _ => Vec::new(),
+    }
+}
+
+/// A value of any type, also from one of the other shapes the API uses for it.
+/// Falls back to the first empty value that `T` accepts (`null`, `0`, `""`, `false`, `[]` or `{}`),
+/// and `None` if `T` accepts none of them.
+pub(crate) fn any<T: DeserializeOwned>(value: Value) -> Option<T> {
+    let empty = [
+        Value::Null,
+        Value::from(0),
+        Value::String(String::new()),
+        Value::Bool(false),
+        Value::Array(Vec::new()),
+        Value::Object(Default::default()),
+    ];
+
+    T::deserialize(&value).ok().or_else(|| {
+        other_shapes(&value)
+            .iter()
+            .chain(empty.iter())
+            .find_map(|shape| T::deserialize(shape).ok())
+    })
+}
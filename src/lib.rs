@@ -1,341 +1,819 @@
-use std::time::Duration;
+// Without a runtime nor the `blocking` feature only the data types are left, the request helpers are unused
+#![cfg_attr(
+    not(any(
+        feature = "runtime-tokio",
+        feature = "runtime-async-std",
+        feature = "blocking"
+    )),
+    allow(dead_code, unused_imports)
+)]
 
-use reqwest::{header::HeaderMap, StatusCode};
+use std::{fs, io, path::Path};
 
+use reqwest::header::HeaderMap;
+
+/// Items that need one of the async runtimes, everything else also works with only the `blocking` feature
+macro_rules! cfg_runtime {
+    ($($item:item)*) => {
+        $(
+            #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+            $item
+        )*
+    };
+}
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod client;
 pub mod data_types;
+#[cfg(any(feature = "mock", feature = "test-util"))]
+mod date;
+mod error;
+#[cfg(feature = "test-util")]
+pub mod fake;
+pub mod games;
 mod http;
+mod keys;
+mod lenient;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod player_name;
+mod request;
+pub mod session;
+pub mod transport;
+pub mod vcr;
+
+cfg_runtime! {
+    mod rt;
+    pub mod watch;
+
+    pub use client::ApexClient;
+}
+
+pub use error::Error;
 
 /// Default time to wait after a 429 error code
 pub const DEFAULT_RATE: f32 = 3.0;
 
-/// Simple function to standarize error messages given a Status Code
-fn handle_error<T>(status: StatusCode) -> Result<T, String> {
-    // Documentation: https://apexlegendsapi.com/#errors
-    match status {
-        StatusCode::TOO_MANY_REQUESTS => Err(String::from(
-            "Too many requests: wait 1 or 2 seconds please",
-        )),
-        StatusCode::UNAUTHORIZED => Err(String::from(
-            "The API key is incorrect, please contact the bot administrator",
-        )),
-        StatusCode::NOT_FOUND => Err(String::from(
-            "Either apexlegendsapi.com is not available or the user does not exist",
-        )),
-        StatusCode::INTERNAL_SERVER_ERROR => {
-            Err(String::from("There was an internal server error"))
-        }
-        _ => Err(format!("{}", status)),
-    }
-}
+/// Longest time to wait after a 429 error code, longer values of the `x-current-rate` header are ignored
+const MAX_RATE: f32 = 300.0;
 
 /// Given an optional header, return the value of the header if it exists or DEFAULT_RATE
-/// The header is `x-current-rate`
+/// The header is `x-current-rate`, its value is only used if it is a number of seconds between 0 and MAX_RATE
 fn get_rate(header: Option<HeaderMap>) -> f32 {
-    if let Some(h) = header {
-        if let Some(value) = h.get("x-current-rate") {
-            let default = DEFAULT_RATE.to_string();
-            let string_value = value.to_str().unwrap_or(default.as_str());
-
-            return string_value.parse().unwrap_or(DEFAULT_RATE);
-        } else {
-            return DEFAULT_RATE;
-        }
-    } else {
-        return DEFAULT_RATE;
-    }
+    header
+        .as_ref()
+        .and_then(|h| h.get("x-current-rate"))
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<f32>().ok())
+        // Also rules out NaN and the infinities
+        .filter(|rate| (0.0..=MAX_RATE).contains(rate))
+        .unwrap_or(DEFAULT_RATE)
 }
 
-/// Gets information about a User. This version automatically retries if the API returns code 429 (too many requests).
-/// For the sleep time it reads the `x-current-rate` header or uses the DEFAULT_RATE
-/// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
-///
-/// # Arguments
-///
-/// * `username` - The Origin username of the player
-/// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
-/// * `retry` - Wether to retry after a timeout or error out immediately
-///
-/// # Examples
-/// ```
-/// use std::env;
-///
-/// #[tokio::test]
-/// async fn user() {
-///     dotenv::dotenv().expect("Could not load .env file");
-///
-///     let user_name = env::var("USERNAME").expect("Expected key USERNAME");
-///     let api_key = env::var("API_KEY").expect("Expected key API_KEY");
-///
-///     // This example automatically handles the 429 error code (too many requests)
-///     match apex_legends::get_user_retry(String::from(&user_name), &api_key, true).await {
-///        Ok(data) => {
-///            println!(
-///                "You are level {}, and you have {} kills.",
-///                data.global.level, data.stats.br_kills.value
-///            );
-///        }
-///        Err(e) => {
-///            println!("there was an error!: {}", e);
-///        }
-///     }
-/// }
-/// ```
-pub async fn get_user_retry(
-    username: String,
-    api_key: &str,
-    retry: bool,
-) -> Result<data_types::ApexUser, String> {
-    match http::get_request(format!(
-        "https://api.mozambiquehe.re/bridge?version=5&platform=PC&player={}&auth={}",
-        username, api_key
-    ))
-    .await
-    {
-        Ok(resp) => match serde_json::from_str(&resp) {
-            Ok(data) => Ok(data),
-            Err(e) => Err(format!("{}", e)),
-        },
-        Err(e) => {
-            if let Some(code) = e.0.status() {
-                if code == StatusCode::TOO_MANY_REQUESTS && retry {
-                    let time = get_rate(e.1);
-
-                    tokio::time::sleep(Duration::from_secs_f32(time)).await;
-
-                    get_user(username, api_key).await
-                } else {
-                    handle_error::<data_types::ApexUser>(code)
-                }
-            } else {
-                Err(format!("{}", e.0))
-            }
-        }
-    }
+/// Writes a file, creating its directory if needed.
+/// The bytes go to a temporary file first, which then replaces `path`, so a crash never leaves a truncated file behind.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&tmp, bytes))
+        .and_then(|_| fs::rename(&tmp, path))
 }
 
-/// Gets information about a User. This version does not handle code 429 (too many requests)
-/// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
-///
-/// # Arguments
-///
-/// * `username` - The Origin username of the player
-/// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
-///
-/// # Examples
-/// ```
-/// use std::env;
-///
-/// #[tokio::test]
-/// async fn user() {
-///     dotenv::dotenv().expect("Could not load .env file");
-///
-///     let user_name = env::var("USERNAME").expect("Expected key USERNAME");
-///     let api_key = env::var("API_KEY").expect("Expected key API_KEY");
-///
-///     // This example will fail if the API returns the 429 error code (too many requests)
-///     match apex_legends::get_user(String::from(&user_name), &api_key).await {
-///        Ok(data) => {
-///            println!(
-///                "You are level {}, and you have {} kills.",
-///                data.global.level, data.stats.br_kills.value
-///            );
-///        }
-///        Err(e) => {
-///            println!("there was an error!: {}", e);
-///        }
-///     }
-/// }
-/// ```
-pub async fn get_user(username: String, api_key: &str) -> Result<data_types::ApexUser, String> {
-    match http::get_request(format!(
-        "https://api.mozambiquehe.re/bridge?version=5&platform=PC&player={}&auth={}",
-        username, api_key
-    ))
-    .await
-    {
-        Ok(resp) => match serde_json::from_str(&resp) {
-            Ok(data) => Ok(data),
-            Err(e) => Err(format!("{}", e)),
-        },
-        Err(e) => {
-            if let Some(code) = e.0.status() {
-                handle_error::<data_types::ApexUser>(code)
-            } else {
-                Err(format!("{}", e.0))
-            }
-        }
+cfg_runtime! {
+    /// The root of the API used by the functions of this module
+    #[cfg(not(all(test, feature = "mock")))]
+    fn base_url() -> String {
+        request::BASE_URL.to_string()
     }
-}
 
-/// Gets information about the recent games.
-/// You must be whitelisted to use this API. It has a strict limit of 5 uniques players queried per hour.
-/// See [https://apexlegendsapi.com/#match-history](https://apexlegendsapi.com/#match-history)
-///
-/// * `user_id` - The player's UID
-/// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
-pub async fn get_recent_games(
-    user_id: String,
-    api_key: &str,
-) -> Result<Vec<data_types::ApexGame>, String> {
-    match http::get_request(format!(
-        "https://api.mozambiquehe.re/games?auth={}&uid={}",
-        api_key, user_id
-    ))
-    .await
-    {
-        Ok(resp) => match serde_json::from_str(&resp) {
-            Ok(data) => Ok(data),
-            Err(e) => Err(format!("{}", e)),
-        },
-        Err(e) => {
-            if let Some(code) = e.0.status() {
-                handle_error::<Vec<data_types::ApexGame>>(code)
-            } else {
-                Err("There was an error getting your recent matches.".to_string())
-            }
-        }
+    /// The root of the API used by the functions of this module, a mock server in the tests
+    #[cfg(all(test, feature = "mock"))]
+    fn base_url() -> String {
+        tests::base_url()
     }
-}
 
-/// Returns a player's UID from a given name, but also works with Playstation and Xbox players
-/// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
-///
-/// ## Warning
-/// This function does not work as intended:
-///    It will not retry because the API returns 200 OK instead of code 429,
-///    so there is no way to check if error 429 has occurred
-///
-/// # Parameters
-/// * `user_id` - The player's UID
-/// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
-/// * `retry` - Wether to retry after a timeout or error out immediately
-pub async fn get_uid_from_username_retry(
-    username: String,
-    api_key: &str,
-    retry: bool,
-) -> Result<data_types::ApexProfile, String> {
-    match http::get_request(format!(
-        "https://api.mozambiquehe.re/nametouid?player={}&platform=PC&auth={}",
-        username, api_key
-    ))
-    .await
-    {
-        Ok(resp) => match serde_json::from_str(&resp) {
-            Ok(data) => Ok(data),
-            Err(e) => match serde_json::from_str::<data_types::ApexError>(&resp) {
-                Ok(err) => Err(err.message),
-                Err(_) => Err(format!("{}", e)),
-            },
-        },
-        Err(e) => {
-            if let Some(code) = e.0.status() {
-                if code == StatusCode::TOO_MANY_REQUESTS && retry {
-                    tokio::time::sleep(Duration::from_secs_f32(get_rate(e.1))).await;
-
-                    get_uid_from_username(username, api_key).await
-                } else {
-                    handle_error::<data_types::ApexProfile>(code)
-                }
-            } else {
-                Err("There was an error getting the user id.".to_string())
-            }
-        }
+    /// Creates the client used by the functions of this module and [`games::history`]
+    pub(crate) fn client(api_key: &str, retry: bool) -> Result<ApexClient, String> {
+        ApexClient::builder(api_key)
+            .base_url(&base_url())
+            .retry(retry)
+            .try_build()
+            .map_err(|e| e.to_string())
     }
-}
 
-/// Returns a player's UID from a given name, but also works with Playstation and Xbox players
-/// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
-///
-/// # Parameters
-/// * `user_id` - The player's UID
-/// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
-pub async fn get_uid_from_username(
-    username: String,
-    api_key: &str,
-) -> Result<data_types::ApexProfile, String> {
-    match http::get_request(format!(
-        "https://api.mozambiquehe.re/nametouid?player={}&platform=PC&auth={}",
-        username, api_key
-    ))
-    .await
-    {
-        Ok(resp) => match serde_json::from_str(&resp) {
-            Ok(data) => Ok(data),
-            Err(e) => match serde_json::from_str::<data_types::ApexError>(&resp) {
-                Ok(err) => Err(err.message),
-                Err(_) => Err(format!("{}", e)),
-            },
-        },
-        Err(e) => {
-            if let Some(code) = e.0.status() {
-                handle_error::<data_types::ApexProfile>(code)
-            } else {
-                Err("There was an error getting the user id.".to_string())
-            }
-        }
+    /// Gets information about a User. This version automatically retries if the API returns code 429 (too many requests).
+    /// For the sleep time it reads the `x-current-rate` header or uses the DEFAULT_RATE
+    /// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The Origin username of the player
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    ///
+    /// # Examples
+    /// ```
+    /// use std::env;
+    ///
+    /// #[tokio::test]
+    /// async fn user() {
+    ///     dotenv::dotenv().expect("Could not load .env file");
+    ///
+    ///     let user_name = env::var("USERNAME").expect("Expected key USERNAME");
+    ///     let api_key = env::var("API_KEY").expect("Expected key API_KEY");
+    ///
+    ///     // This example automatically handles the 429 error code (too many requests)
+    ///     match apex_legends::get_user_retry(String::from(&user_name), &api_key, true).await {
+    ///        Ok(data) => {
+    ///            println!(
+    ///                "You are level {}, and you have {} kills.",
+    ///                data.global.level, data.stats.br_kills.value
+    ///            );
+    ///        }
+    ///        Err(e) => {
+    ///            println!("there was an error!: {}", e);
+    ///        }
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_user_retry(
+        username: String,
+        api_key: &str,
+        retry: bool,
+    ) -> Result<data_types::ApexUser, String> {
+        client(api_key, retry)?
+            .get_user(&username)
+            .await
+            .map_err(|e| e.to_string())
     }
-}
 
-/// The map rotation API will return the current and next map for Battle Royale and Arenas, for both pubs and ranked modes.
-/// Control map rotation is also available.
-/// See [https://apexlegendsapi.com/#map-rotation](https://apexlegendsapi.com/#map-rotation)
-///
-/// # Parameters
-/// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
-/// * `retry` - Wether to retry after a timeout or error out immediately
-pub async fn get_map_rotation_retry(
-    api_key: &str,
-    retry: bool,
-) -> Result<data_types::ApexMapRotation, String> {
-    match http::get_request(format!(
-        "https://api.mozambiquehe.re/maprotation?version=2&auth={}",
-        api_key
-    ))
-    .await
-    {
-        Ok(resp) => match serde_json::from_str(&resp) {
-            Ok(data) => Ok(data),
-            Err(_) => Err("Unable to deserialize JSON.".to_string()),
-        },
-        Err(e) => {
-            if let Some(code) = e.0.status() {
-                if code == StatusCode::TOO_MANY_REQUESTS && retry {
-                    tokio::time::sleep(Duration::from_secs_f32(get_rate(e.1))).await;
-
-                    get_map_rotation(api_key).await
-                } else {
-                    handle_error::<data_types::ApexMapRotation>(code)
-                }
-            } else {
-                Err("There was an error getting the map rotation.".to_string())
-            }
-        }
+    /// Gets information about a User. This version does not handle code 429 (too many requests)
+    /// See [https://apexlegendsapi.com/#player-statistics](https://apexlegendsapi.com/#player-statistics)
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The Origin username of the player
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    ///
+    /// # Examples
+    /// ```
+    /// use std::env;
+    ///
+    /// #[tokio::test]
+    /// async fn user() {
+    ///     dotenv::dotenv().expect("Could not load .env file");
+    ///
+    ///     let user_name = env::var("USERNAME").expect("Expected key USERNAME");
+    ///     let api_key = env::var("API_KEY").expect("Expected key API_KEY");
+    ///
+    ///     // This example will fail if the API returns the 429 error code (too many requests)
+    ///     match apex_legends::get_user(String::from(&user_name), &api_key).await {
+    ///        Ok(data) => {
+    ///            println!(
+    ///                "You are level {}, and you have {} kills.",
+    ///                data.global.level, data.stats.br_kills.value
+    ///            );
+    ///        }
+    ///        Err(e) => {
+    ///            println!("there was an error!: {}", e);
+    ///        }
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_user(username: String, api_key: &str) -> Result<data_types::ApexUser, String> {
+        client(api_key, false)?
+            .get_user(&username)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Gets information about the recent games.
+    /// You must be whitelisted to use this API. It has a strict limit of 5 uniques players queried per hour.
+    /// See [https://apexlegendsapi.com/#match-history](https://apexlegendsapi.com/#match-history)
+    ///
+    /// * `user_id` - The player's UID
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub async fn get_recent_games(
+        user_id: String,
+        api_key: &str,
+    ) -> Result<Vec<data_types::ApexGame>, String> {
+        get_games(&games::GamesQuery::new(user_id), api_key).await
+    }
+
+    /// Gets the games matching a query. Use [`games::history`] to walk through several pages.
+    /// You must be whitelisted to use this API. It has a strict limit of 5 uniques players queried per hour.
+    /// See [https://apexlegendsapi.com/#match-history](https://apexlegendsapi.com/#match-history)
+    ///
+    /// * `query` - The player's UID and the filters to apply
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub async fn get_games(
+        query: &games::GamesQuery,
+        api_key: &str,
+    ) -> Result<Vec<data_types::ApexGame>, String> {
+        client(api_key, false)?
+            .get_games(query)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns a player's UID from a given PC name.
+    /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+    ///
+    /// ## Warning
+    /// This function does not work as intended:
+    ///    It will not retry because the API returns 200 OK instead of code 429,
+    ///    so there is no way to check if error 429 has occurred
+    ///
+    /// # Parameters
+    /// * `username` - The player's name
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    pub async fn get_uid_from_username_retry(
+        username: String,
+        api_key: &str,
+        retry: bool,
+    ) -> Result<data_types::ApexProfile, String> {
+        get_platform_uid_from_username_retry(username, data_types::Platform::Pc, api_key, retry).await
+    }
+
+    /// Returns a player's UID from a given PC name.
+    /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+    ///
+    /// # Parameters
+    /// * `username` - The player's name
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub async fn get_uid_from_username(
+        username: String,
+        api_key: &str,
+    ) -> Result<data_types::ApexProfile, String> {
+        get_platform_uid_from_username(username, data_types::Platform::Pc, api_key).await
+    }
+
+    /// Returns a player's UID from a given name on the given platform.
+    /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+    ///
+    /// ## Warning
+    /// This function does not work as intended:
+    ///    It will not retry because the API returns 200 OK instead of code 429,
+    ///    so there is no way to check if error 429 has occurred
+    ///
+    /// # Parameters
+    /// * `username` - The player's name
+    /// * `platform` - The platform of the player
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    pub async fn get_platform_uid_from_username_retry(
+        username: String,
+        platform: data_types::Platform,
+        api_key: &str,
+        retry: bool,
+    ) -> Result<data_types::ApexProfile, String> {
+        client(api_key, retry)?
+            .get_uid_from_username(&username, platform)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns a player's UID from a given name on the given platform.
+    /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+    ///
+    /// # Parameters
+    /// * `username` - The player's name
+    /// * `platform` - The platform of the player
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub async fn get_platform_uid_from_username(
+        username: String,
+        platform: data_types::Platform,
+        api_key: &str,
+    ) -> Result<data_types::ApexProfile, String> {
+        client(api_key, false)?
+            .get_uid_from_username(&username, platform)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Looks up a name on every platform and returns all the matches, each with the platform it was found on.
+    /// An empty list means the name was not found anywhere.
+    /// See [https://apexlegendsapi.com/#name-to-uid](https://apexlegendsapi.com/#name-to-uid)
+    ///
+    /// # Parameters
+    /// * `username` - The player's name
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    pub async fn find_uids_from_username(
+        username: String,
+        api_key: &str,
+        retry: bool,
+    ) -> Result<Vec<data_types::PlatformProfile>, String> {
+        client(api_key, retry)?
+            .find_uids_from_username(&username)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// The map rotation API will return the current and next map for Battle Royale and Arenas, for both pubs and ranked modes.
+    /// Control map rotation is also available.
+    /// See [https://apexlegendsapi.com/#map-rotation](https://apexlegendsapi.com/#map-rotation)
+    ///
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    pub async fn get_map_rotation_retry(
+        api_key: &str,
+        retry: bool,
+    ) -> Result<data_types::ApexMapRotation, String> {
+        client(api_key, retry)?
+            .get_map_rotation()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// The map rotation API will return the current and next map for Battle Royale and Arenas, for both pubs and ranked modes.
+    /// Control map rotation is also available.
+    /// See [https://apexlegendsapi.com/#map-rotation](https://apexlegendsapi.com/#map-rotation)
+    ///
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub async fn get_map_rotation(api_key: &str) -> Result<data_types::ApexMapRotation, String> {
+        client(api_key, false)?
+            .get_map_rotation()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns the status of Origin login, EA accounts, crossplay auth and the Apex Legends servers in every region.
+    /// See [https://apexlegendsapi.com/#server-status](https://apexlegendsapi.com/#server-status)
+    ///
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    pub async fn get_server_status_retry(
+        api_key: &str,
+        retry: bool,
+    ) -> Result<data_types::ServerStatus, String> {
+        client(api_key, retry)?
+            .get_server_status()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns the status of Origin login, EA accounts, crossplay auth and the Apex Legends servers in every region.
+    /// See [https://apexlegendsapi.com/#server-status](https://apexlegendsapi.com/#server-status)
+    ///
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub async fn get_server_status(api_key: &str) -> Result<data_types::ServerStatus, String> {
+        client(api_key, false)?
+            .get_server_status()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns the points needed to reach Apex Predator and the number of Masters and Predators,
+    /// for Battle Royale and Arenas on every platform.
+    /// See [https://apexlegendsapi.com/#predator](https://apexlegendsapi.com/#predator)
+    ///
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    /// * `retry` - Wether to retry after a timeout or error out immediately
+    pub async fn get_predator_thresholds_retry(
+        api_key: &str,
+        retry: bool,
+    ) -> Result<data_types::PredatorThresholds, String> {
+        client(api_key, retry)?
+            .get_predator_thresholds()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns the points needed to reach Apex Predator and the number of Masters and Predators,
+    /// for Battle Royale and Arenas on every platform.
+    /// See [https://apexlegendsapi.com/#predator](https://apexlegendsapi.com/#predator)
+    ///
+    /// # Parameters
+    /// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
+    pub async fn get_predator_thresholds(
+        api_key: &str,
+    ) -> Result<data_types::PredatorThresholds, String> {
+        client(api_key, false)?
+            .get_predator_thresholds()
+            .await
+            .map_err(|e| e.to_string())
     }
 }
 
-/// The map rotation API will return the current and next map for Battle Royale and Arenas, for both pubs and ranked modes.
-/// Control map rotation is also available.
-/// See [https://apexlegendsapi.com/#map-rotation](https://apexlegendsapi.com/#map-rotation)
-///
-/// # Parameters
-/// * `api_key` - The API key for [https://apexlegendsstatus.com](https://apexlegendsstatus.com)
-pub async fn get_map_rotation(api_key: &str) -> Result<data_types::ApexMapRotation, String> {
-    match http::get_request(format!(
-        "https://api.mozambiquehe.re/maprotation?version=2&auth={}",
-        api_key
-    ))
-    .await
-    {
-        Ok(resp) => match serde_json::from_str(&resp) {
-            Ok(data) => Ok(data),
-            Err(_) => Err("Unable to deserialize JSON.".to_string()),
-        },
-        Err(e) => {
-            if let Some(code) = e.0.status() {
-                handle_error::<data_types::ApexMapRotation>(code)
-            } else {
-                Err("There was an error getting the map rotation.".to_string())
-            }
+cfg_runtime! {
+    #[cfg(all(test, feature = "mock"))]
+    mod tests {
+        use std::{cell::RefCell, future::Future};
+
+        use futures::TryStreamExt;
+
+        use crate::{
+            data_types::Platform,
+            games::{self, GamesQuery},
+            mock::{MockGame, MockPlayer, MockServer, RateLimit, RateLimitStyle},
+            transport::StatusCode,
+            Error,
+        };
+
+        thread_local! {
+            /// The mock server of the test running on this thread
+            static BASE_URL: RefCell<Option<String>> = const { RefCell::new(None) };
+        }
+
+        pub(super) fn base_url() -> String {
+            BASE_URL
+                .with(|url| url.borrow().clone())
+                .unwrap_or_else(|| crate::request::BASE_URL.to_string())
+        }
+
+        /// A mock server with a player on PC and one on PlayStation, used by the functions of this module on this thread.
+        /// Each test runs on its own thread, in a single threaded runtime.
+        fn server() -> MockServer {
+            let server = MockServer::start();
+            let lifeline = MockPlayer::new("HeyImLifeline", Platform::Pc).level(512);
+
+            server.set_games(
+                lifeline.uid,
+                vec![
+                    MockGame::new("Lifeline", 1637597100),
+                    MockGame::new("Bloodhound", 1637595000),
+                ],
+            );
+            server.add_player(lifeline);
+            server.add_player(MockPlayer::new("HeyImBangalore", Platform::Playstation));
+            BASE_URL.with(|url| *url.borrow_mut() = Some(server.url().to_string()));
+
+            server
+        }
+
+        fn uid() -> String {
+            MockPlayer::new("HeyImLifeline", Platform::Pc)
+                .uid
+                .to_string()
+        }
+
+        fn too_many_requests() -> String {
+            Error::Status(StatusCode::TOO_MANY_REQUESTS).to_string()
+        }
+
+        /// Limits the API key to the `requests` sent by each call
+        fn limit(server: &MockServer, requests: usize) {
+            server.set_rate_limit(Some(RateLimit {
+                per_second: requests,
+                style: RateLimitStyle::TooManyRequests,
+            }));
+        }
+
+        /// Checks that a function sending `requests` requests does not retry after a 429
+        async fn check_no_retry<T, F, Fut>(server: &MockServer, requests: usize, call: F)
+        where
+            F: Fn() -> Fut,
+            Fut: Future<Output = Result<T, String>>,
+        {
+            limit(server, requests);
+
+            assert!(call().await.is_ok());
+            assert_eq!(call().await.err(), Some(too_many_requests()));
+        }
+
+        /// Checks that a function sending `requests` requests only waits after a 429 when `retry` is set
+        async fn check_retry<T, F, Fut>(server: &MockServer, requests: usize, call: F)
+        where
+            F: Fn(bool) -> Fut,
+            Fut: Future<Output = Result<T, String>>,
+        {
+            check_no_retry(server, requests, || call(false)).await;
+
+            assert!(call(true).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn get_user_retry() {
+            let server = server();
+
+            let user = super::get_user_retry("HeyImLifeline".to_string(), "key", false).await;
+            assert_eq!(user.unwrap().global.level, 512);
+            assert_eq!(
+                super::get_user_retry("HeyImNobody".to_string(), "key", true)
+                    .await
+                    .err(),
+                Some("Player HeyImNobody not found".to_string())
+            );
+
+            check_retry(&server, 1, |retry| {
+                super::get_user_retry("HeyImLifeline".to_string(), "key", retry)
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_user() {
+            let server = server();
+
+            let user = super::get_user("HeyImLifeline".to_string(), "key").await;
+            assert_eq!(user.unwrap().global.level, 512);
+            assert_eq!(
+                super::get_user("".to_string(), "key").await.err(),
+                Some("Invalid player name: the name is empty".to_string())
+            );
+
+            check_no_retry(&server, 1, || {
+                super::get_user("HeyImLifeline".to_string(), "key")
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_recent_games() {
+            let server = server();
+
+            assert_eq!(
+                super::get_recent_games(uid(), "key").await.unwrap().len(),
+                2
+            );
+            assert_eq!(
+                super::get_recent_games("1".to_string(), "key").await.err(),
+                Some("Player 1 not found".to_string())
+            );
+
+            check_no_retry(&server, 1, || super::get_recent_games(uid(), "key")).await;
+        }
+
+        #[tokio::test]
+        async fn get_games() {
+            let server = server();
+            let query = GamesQuery::new(uid()).limit(1);
+
+            let games = super::get_games(&query, "key").await.unwrap();
+            assert_eq!(games.len(), 1);
+            assert_eq!(games[0].legend_played, "Lifeline");
+
+            check_no_retry(&server, 1, || super::get_games(&query, "key")).await;
+        }
+
+        #[tokio::test]
+        async fn history_retry() {
+            let server = server();
+
+            let games: Vec<_> = games::history_retry(GamesQuery::new(uid()).limit(1), "key", 0, false)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(games.len(), 2);
+
+            check_retry(&server, 1, |retry| {
+                games::history_retry(GamesQuery::new(uid()), "key", 0, retry).try_collect::<Vec<_>>()
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn history() {
+            let server = server();
+
+            let games: Vec<_> = games::history(GamesQuery::new(uid()).limit(1), "key", 0)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(games.len(), 2);
+
+            let games: Result<Vec<_>, _> = games::history(GamesQuery::new("1"), "key", 0)
+                .try_collect()
+                .await;
+            assert_eq!(games.err(), Some("Player 1 not found".to_string()));
+
+            check_no_retry(&server, 1, || {
+                games::history(GamesQuery::new(uid()), "key", 0).try_collect::<Vec<_>>()
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_uid_from_username_retry() {
+            let server = server();
+
+            let profile =
+                super::get_uid_from_username_retry("HeyImLifeline".to_string(), "key", false).await;
+            assert_eq!(profile.unwrap().uid, uid());
+            // Only looks on PC
+            assert_eq!(
+                super::get_uid_from_username_retry("HeyImBangalore".to_string(), "key", false)
+                    .await
+                    .err(),
+                Some("Player HeyImBangalore not found".to_string())
+            );
+
+            check_retry(&server, 1, |retry| {
+                super::get_uid_from_username_retry("HeyImLifeline".to_string(), "key", retry)
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_uid_from_username() {
+            let server = server();
+
+            let profile = super::get_uid_from_username("HeyImLifeline".to_string(), "key").await;
+            assert_eq!(profile.unwrap().uid, uid());
+            assert_eq!(
+                super::get_uid_from_username("HeyImBangalore".to_string(), "key")
+                    .await
+                    .err(),
+                Some("Player HeyImBangalore not found".to_string())
+            );
+
+            check_no_retry(&server, 1, || {
+                super::get_uid_from_username("HeyImLifeline".to_string(), "key")
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_platform_uid_from_username_retry() {
+            let server = server();
+
+            let profile = super::get_platform_uid_from_username_retry(
+                "HeyImBangalore".to_string(),
+                Platform::Playstation,
+                "key",
+                false,
+            )
+            .await;
+            assert_eq!(profile.unwrap().name, "HeyImBangalore");
+            assert_eq!(
+                super::get_platform_uid_from_username_retry(
+                    "HeyImBangalore".to_string(),
+                    Platform::Xbox,
+                    "key",
+                    false,
+                )
+                .await
+                .err(),
+                Some("Player HeyImBangalore not found".to_string())
+            );
+
+            check_retry(&server, 1, |retry| {
+                super::get_platform_uid_from_username_retry(
+                    "HeyImBangalore".to_string(),
+                    Platform::Playstation,
+                    "key",
+                    retry,
+                )
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_platform_uid_from_username() {
+            let server = server();
+
+            let profile = super::get_platform_uid_from_username(
+                "HeyImBangalore".to_string(),
+                Platform::Playstation,
+                "key",
+            )
+            .await;
+            assert_eq!(profile.unwrap().name, "HeyImBangalore");
+            assert_eq!(
+                super::get_platform_uid_from_username(
+                    "HeyImBangalore".to_string(),
+                    Platform::Pc,
+                    "key"
+                )
+                .await
+                .err(),
+                Some("Player HeyImBangalore not found".to_string())
+            );
+
+            check_no_retry(&server, 1, || {
+                super::get_platform_uid_from_username(
+                    "HeyImBangalore".to_string(),
+                    Platform::Playstation,
+                    "key",
+                )
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn find_uids_from_username() {
+            let server = server();
+
+            let found = super::find_uids_from_username("HeyImBangalore".to_string(), "key", false)
+                .await
+                .unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].platform, Platform::Playstation);
+            assert!(
+                super::find_uids_from_username("HeyImNobody".to_string(), "key", false)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+
+            // One request per platform
+            check_retry(&server, Platform::ALL.len(), |retry| {
+                super::find_uids_from_username("HeyImBangalore".to_string(), "key", retry)
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_map_rotation_retry() {
+            let server = server();
+
+            let rotation = super::get_map_rotation_retry("key", false).await.unwrap();
+            assert_eq!(rotation.battle_royale.current.map, "Olympus");
+
+            check_retry(&server, 1, |retry| {
+                super::get_map_rotation_retry("key", retry)
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_map_rotation() {
+            let server = server();
+
+            let rotation = super::get_map_rotation("key").await.unwrap();
+            assert_eq!(rotation.arenas.current.map, "Phase Runner");
+
+            server.set_api_keys(&["other-key"]);
+            assert_eq!(
+                super::get_map_rotation("key").await.err(),
+                Some(Error::Status(StatusCode::UNAUTHORIZED).to_string())
+            );
+            server.set_api_keys(&["key"]);
+
+            check_no_retry(&server, 1, || super::get_map_rotation("key")).await;
+        }
+
+        #[tokio::test]
+        async fn get_server_status_retry() {
+            let server = server();
+
+            let status = super::get_server_status_retry("key", false).await.unwrap();
+            assert!(!status.is_anything_down(&crate::data_types::Region::EuWest));
+
+            check_retry(&server, 1, |retry| {
+                super::get_server_status_retry("key", retry)
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_server_status() {
+            let server = server();
+
+            let status = super::get_server_status("key").await.unwrap();
+            assert!(!status.is_anything_down(&crate::data_types::Region::Asia));
+
+            server.set_response(crate::client::Endpoint::ServerStatus, 200, "[]");
+            assert!(super::get_server_status("key")
+                .await
+                .unwrap_err()
+                .starts_with("Unable to deserialize JSON"));
+            server.clear_response(crate::client::Endpoint::ServerStatus);
+
+            check_no_retry(&server, 1, || super::get_server_status("key")).await;
+        }
+
+        #[tokio::test]
+        async fn get_predator_thresholds_retry() {
+            let server = server();
+
+            let thresholds = super::get_predator_thresholds_retry("key", false)
+                .await
+                .unwrap();
+            assert_eq!(thresholds.battle_royale.pc.value, 15000);
+
+            check_retry(&server, 1, |retry| {
+                super::get_predator_thresholds_retry("key", retry)
+            })
+            .await;
+        }
+
+        #[tokio::test]
+        async fn get_predator_thresholds() {
+            let server = server();
+
+            let thresholds = super::get_predator_thresholds("key").await.unwrap();
+            assert_eq!(thresholds.arenas.pc.value, 9500);
+
+            server.set_response(crate::client::Endpoint::Predator, 500, "");
+            assert_eq!(
+                super::get_predator_thresholds("key").await.err(),
+                Some("There was an internal server error".to_string())
+            );
+            server.clear_response(crate::client::Endpoint::Predator);
+
+            check_no_retry(&server, 1, || super::get_predator_thresholds("key")).await;
         }
     }
 }
@@ -0,0 +1,433 @@
+//! `apex`, a command-line tool to look up players and the map rotation.
+//!
+//! Requires the `cli` feature: `cargo install apex_legends_api --features cli`
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::PathBuf,
+    process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use apex_legends_api::{
+    cache::CacheConfig,
+    client::Endpoint,
+    data_types::{
+        ApexGame, ApexMapRotation, ApexMapRotationData, ApexRank, ApexUser, Platform,
+        PlatformProfile,
+    },
+    games::GamesQuery,
+    watch::MapRotationEvent,
+    ApexClient,
+};
+use clap::{Parser, Subcommand};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+const CONFIG_HELP: &str = "\
+The API key is read from the APEX_API_KEY environment variable, or from the config file:
+
+    api_key = \"your_api_key\"
+    # Optional
+    platform = \"PS4\"
+    base_url = \"https://api.mozambiquehe.re\"
+
+The config file is ~/.config/apex/config.toml by default, or the one given by --config or APEX_CONFIG.
+APEX_BASE_URL overrides the base_url of the config file.";
+
+/// Looks up Apex Legends players and the map rotation, using https://apexlegendsstatus.com
+#[derive(Parser)]
+#[command(name = "apex", version, after_help = CONFIG_HELP)]
+struct Cli {
+    /// The platform of the player: PC, PS4, X1 or SWITCH [default: the one of the config file, or PC]
+    #[arg(long, short, global = true)]
+    platform: Option<Platform>,
+    /// Print the results as JSON. The keys are the snake_case field names of the data types of the
+    /// apex_legends_api crate (e.g. rank_score), not the camelCase keys of the API (rankScore)
+    #[arg(long, global = true)]
+    json: bool,
+    /// The config file
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Shows the level, ranks and status of a player
+    User { name: String },
+    /// Finds the UID of a player, on every platform unless --platform is given
+    Uid { name: String },
+    /// Lists the recent games of a player. The API key must be whitelisted for the match history
+    Games {
+        /// The name of the player, or their UID with --uid
+        player: String,
+        /// PLAYER is a UID rather than a name
+        #[arg(long)]
+        uid: bool,
+        /// The maximum number of games
+        #[arg(long, short, default_value_t = 10)]
+        limit: u32,
+    },
+    /// Shows the current and next map of every mode
+    Maps {
+        /// Keep the maps on screen with a live countdown, fetching them again when they change.
+        /// With --json, prints the rotation on a new line each time a map changes
+        #[arg(long, short)]
+        watch: bool,
+    },
+}
+
+#[derive(Deserialize, Default)]
+struct Config {
+    api_key: Option<String>,
+    platform: Option<String>,
+    base_url: Option<String>,
+}
+
+/// `~/.config/apex/config.toml`, or its equivalent on Windows
+fn default_config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(dir.join("apex").join("config.toml"))
+}
+
+/// Reads the config file. The default one may not exist, but a file given explicitly must.
+fn load_config(path: Option<PathBuf>) -> Result<Config, String> {
+    let (path, explicit) = match path.or_else(|| env::var_os("APEX_CONFIG").map(PathBuf::from)) {
+        Some(path) => (path, true),
+        None => match default_config_path() {
+            Some(path) => (path, false),
+            None => return Ok(Config::default()),
+        },
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(_) if !explicit && !path.exists() => Ok(Config::default()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// e.g. `1h 15m`, `3m 20s`
+fn duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, _) if h < 48 => format!("{}h {}m", h, m),
+        (h, _, _) => format!("{} days", h / 24),
+    }
+}
+
+/// Aligns the columns of `rows`
+fn table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+
+            line.join("  ").trim_end().to_string() + "\n"
+        })
+        .collect()
+}
+
+fn row<const N: usize>(cells: [&str; N]) -> Vec<String> {
+    cells.iter().map(|cell| cell.to_string()).collect()
+}
+
+fn rank(rank: &ApexRank, points: &str) -> String {
+    match (rank.rank_name.as_str(), rank.rank_division) {
+        ("", _) => "Unranked".to_string(),
+        (name, 0) => format!("{} ({} {})", name, rank.rank_score, points),
+        (name, division) => format!("{} {} ({} {})", name, division, rank.rank_score, points),
+    }
+}
+
+fn user_table(user: &ApexUser) -> String {
+    let global = &user.global;
+    let status = if user.realtime.is_online == 0 {
+        "Offline"
+    } else if user.realtime.is_in_game != 0 {
+        "In a match"
+    } else {
+        "In the lobby"
+    };
+    let ban = if global.bans.is_active {
+        format!(
+            "{} left ({})",
+            duration(global.bans.remaining_seconds as i64),
+            global.bans.last_ban_reason
+        )
+    } else {
+        "No".to_string()
+    };
+
+    table(&[
+        row(["Name", &global.name]),
+        row(["UID", &global.uid.to_string()]),
+        row(["Platform", &global.platform]),
+        row([
+            "Level",
+            &format!("{} ({}%)", global.level, global.to_next_level_percent),
+        ]),
+        row(["Battle Royale", &rank(&global.rank, "RP")]),
+        row(["Arenas", &rank(&global.arena, "AP")]),
+        row(["Status", status]),
+        row(["Legend", &user.realtime.selected_legend]),
+        row(["Banned", &ban]),
+    ])
+}
+
+fn games_table(games: &[ApexGame]) -> String {
+    let mut rows = vec![row(["Ended", "Mode", "Legend", "Length", "Kills", "Score"])];
+
+    for game in games {
+        let (mode, change) = match game.game_mode.as_str() {
+            "BATTLE_ROYALE" => ("Battle Royale", game.br_score_change),
+            "ARENAS" => ("Arenas", game.arenas_score_change),
+            mode => (mode, 0),
+        };
+        let kills = game
+            .game_data
+            .iter()
+            .find(|data| data.key.ends_with("kills"))
+            .map_or("-".to_string(), |data| data.value.to_string());
+
+        rows.push(row([
+            &format!("{} ago", duration(now() - game.game_end_timestamp as i64)),
+            mode,
+            &game.legend_played,
+            &duration(game.game_length_seconds as i64),
+            &kills,
+            &format!("{:+}", change),
+        ]));
+    }
+
+    table(&rows)
+}
+
+fn maps_table(rotation: &ApexMapRotation) -> String {
+    let mut rows = vec![row(["Mode", "Current", "Time left", "Next"])];
+    let mut push = |mode: &str, data: &ApexMapRotationData| {
+        if !data.current.map.is_empty() {
+            rows.push(row([
+                mode,
+                &data.current.map,
+                &duration(data.current.end - now()),
+                &data.next.map,
+            ]));
+        }
+    };
+
+    push("Battle Royale", &rotation.battle_royale);
+    push("Arenas", &rotation.arenas);
+    push("Ranked Arenas", &rotation.arenas_ranked);
+    if !rotation.ranked.current.map.is_empty() {
+        rows.push(row([
+            "Ranked",
+            &rotation.ranked.current.map,
+            "-",
+            &rotation.ranked.next.map,
+        ]));
+    }
+
+    table(&rows)
+}
+
+fn print<T: Serialize>(
+    json: bool,
+    data: &T,
+    human: impl FnOnce(&T) -> String,
+) -> Result<(), String> {
+    if json {
+        let json = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
+        println!("{}", json);
+    } else {
+        print!("{}", human(data));
+    }
+
+    Ok(())
+}
+
+/// Redraws the map rotation every second, and only fetches it again when the current map of a mode ends.
+/// With `json`, prints the rotation each time it changes.
+async fn watch_maps(client: &ApexClient, json: bool) -> Result<(), String> {
+    let mut events = Box::pin(client.watch_map_rotation());
+    let mut rotation: Option<ApexMapRotation> = None;
+    let mut error = None;
+    let mut printed = String::new();
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                // The rotation fetched by the watcher is in the cache, getting it again does not send a request
+                let fetched = match event {
+                    Some(Ok(MapRotationEvent::Started(started))) => Ok(*started),
+                    Some(Ok(MapRotationEvent::Changed(_))) => client.get_map_rotation().await,
+                    Some(Err(e)) => Err(e),
+                    None => return Ok(()),
+                };
+
+                match fetched {
+                    Ok(fetched) => {
+                        let line = serde_json::to_string(&fetched).map_err(|e| e.to_string())?;
+                        if json && line != printed {
+                            println!("{}", line);
+                            printed = line;
+                        }
+
+                        rotation = Some(fetched);
+                        error = None;
+                    }
+                    Err(e) => {
+                        if json {
+                            eprintln!("Error: {}", e);
+                        }
+
+                        error = Some(e.to_string());
+                    }
+                }
+            }
+            _ = tokio::time::sleep(Duration::from_secs(1)), if !json => {}
+        }
+
+        if !json {
+            // Clear the screen and draw from the top left corner
+            let mut screen = "\x1b[2J\x1b[H".to_string();
+            if let Some(rotation) = &rotation {
+                screen += &maps_table(rotation);
+            }
+            if let Some(error) = &error {
+                screen += &format!("\nError: {} (retrying)\n", error);
+            }
+
+            print!("{}", screen);
+        }
+        let _ = io::stdout().flush();
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+    let config = load_config(cli.config)?;
+
+    let api_key = env::var("APEX_API_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .or(config.api_key)
+        .ok_or("No API key, set APEX_API_KEY or add it to the config file (see apex --help)")?;
+    let platform = match (cli.platform, config.platform) {
+        (Some(platform), _) => platform,
+        (None, Some(platform)) => platform.parse()?,
+        (None, None) => Platform::Pc,
+    };
+
+    // Keeps the map rotation until it changes, so `maps --watch` can read the one its watcher fetched
+    let cache = CacheConfig::empty().ttl(Endpoint::MapRotation, Duration::from_secs(60 * 60));
+    let mut builder = ApexClient::builder(&api_key).retry(true).cache(cache);
+    if let Some(base_url) = env::var("APEX_BASE_URL").ok().or(config.base_url) {
+        builder = builder.base_url(&base_url);
+    }
+    let client = builder.try_build().map_err(|e| e.to_string())?;
+
+    match cli.command {
+        Command::User { name } => {
+            let user = client
+                .get_user_on_platform(&name, platform)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            print(cli.json, &user, user_table)?;
+        }
+        Command::Uid { name } => {
+            let found = match cli.platform {
+                Some(platform) => vec![PlatformProfile {
+                    platform,
+                    profile: client
+                        .get_uid_from_username(&name, platform)
+                        .await
+                        .map_err(|e| e.to_string())?,
+                }],
+                None => client
+                    .find_uids_from_username(&name)
+                    .await
+                    .map_err(|e| e.to_string())?,
+            };
+            if found.is_empty() {
+                return Err(format!("Player {} not found on any platform", name));
+            }
+
+            print(cli.json, &found, |found| {
+                let mut rows = vec![row(["Platform", "Name", "UID"])];
+                for found in found {
+                    rows.push(row([
+                        found.platform.as_str(),
+                        &found.profile.name,
+                        &found.profile.uid,
+                    ]));
+                }
+
+                table(&rows)
+            })?;
+        }
+        Command::Games { player, uid, limit } => {
+            let uid = if uid {
+                player
+            } else {
+                client
+                    .get_uid_from_username(&player, platform)
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .uid
+            };
+            let games = client
+                .get_games(&GamesQuery::new(uid).limit(limit))
+                .await
+                .map_err(|e| e.to_string())?;
+
+            print(cli.json, &games, |games| games_table(games))?;
+        }
+        Command::Maps { watch: true } => watch_maps(&client, cli.json).await?,
+        Command::Maps { watch: false } => {
+            let rotation = client.get_map_rotation().await.map_err(|e| e.to_string())?;
+
+            print(cli.json, &rotation, maps_table)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
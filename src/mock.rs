@@ -0,0 +1,868 @@
+//! An in-process mock of the API, to test code built on this crate without the real API.
+//!
+//! Requires the `mock` feature. The server runs on its own threads, so it works with any async runtime
+//! and with the blocking client. It stops when the [`MockServer`] is dropped.
+//!
+//! # Examples
+//! ```
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! use apex_legends_api::{
+//!     data_types::Platform,
+//!     mock::{MockPlayer, MockServer},
+//!     ApexClient,
+//! };
+//!
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockServer::start();
+//!     server.add_player(MockPlayer::new("HeyImLifeline", Platform::Pc).level(512));
+//!
+//!     let client = ApexClient::builder("any_key").base_url(server.url()).build();
+//!     let user = client.get_user("HeyImLifeline").await.unwrap();
+//!
+//!     assert_eq!(user.global.level, 512);
+//! }
+//! # #[cfg(not(any(feature = "runtime-tokio", feature = "runtime-async-std")))]
+//! # fn main() {}
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use url::form_urlencoded;
+
+use crate::{
+    client::Endpoint,
+    data_types::{Platform, Region},
+    date::readable_date,
+};
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// A ranked tier, as shown in the player statistics
+#[derive(Debug, Clone)]
+pub struct MockRank {
+    pub score: i32,
+    pub name: String,
+    pub division: i32,
+}
+
+impl MockRank {
+    pub fn new(score: i32, name: &str, division: i32) -> Self {
+        Self {
+            score,
+            name: name.to_string(),
+            division,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let image = match self.name.as_str() {
+            "Master" | "Apex Predator" | "Unranked" => self.name.to_lowercase().replace(' ', ""),
+            name => format!("{}{}", name.to_lowercase(), self.division),
+        };
+
+        json!({
+            "rankScore": self.score,
+            "rankName": self.name,
+            "rankDiv": self.division,
+            "ladderPosPlatform": -1,
+            "rankImg": format!("https://api.mozambiquehe.re/assets/ranks/{}.png", image),
+            "rankedSeason": "season11_split_1",
+        })
+    }
+}
+
+/// A player known by the mock server
+#[derive(Debug, Clone)]
+pub struct MockPlayer {
+    pub name: String,
+    pub uid: i64,
+    pub platform: Platform,
+    pub level: i32,
+    pub to_next_level_percent: i32,
+    pub rank: MockRank,
+    pub arena: MockRank,
+    /// Reason and remaining seconds of an active ban
+    pub ban: Option<(String, i32)>,
+    pub online: bool,
+    pub in_game: bool,
+    pub can_join: bool,
+    pub party_full: bool,
+    pub selected_legend: String,
+    pub kills: i32,
+    pub damage: i32,
+    pub games_played: i32,
+}
+
+impl MockPlayer {
+    /// An online player in the lobby, with a UID derived from the name
+    pub fn new(name: &str, platform: Platform) -> Self {
+        let uid = name.bytes().fold(1000000000000i64, |uid, byte| {
+            (uid * 31 + byte as i64) % 9000000000000 + 1000000000000
+        });
+
+        Self {
+            name: name.to_string(),
+            uid,
+            platform,
+            level: 100,
+            to_next_level_percent: 50,
+            rank: MockRank::new(4000, "Gold", 4),
+            arena: MockRank::new(1600, "Silver", 2),
+            ban: None,
+            online: true,
+            in_game: false,
+            can_join: true,
+            party_full: false,
+            selected_legend: "Lifeline".to_string(),
+            kills: 1000,
+            damage: 250000,
+            games_played: 500,
+        }
+    }
+
+    pub fn uid(mut self, uid: i64) -> Self {
+        self.uid = uid;
+        self
+    }
+
+    pub fn level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn to_next_level_percent(mut self, percent: i32) -> Self {
+        self.to_next_level_percent = percent;
+        self
+    }
+
+    /// The Battle Royale rank
+    pub fn rank(mut self, rank: MockRank) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    /// The Arenas rank
+    pub fn arena(mut self, rank: MockRank) -> Self {
+        self.arena = rank;
+        self
+    }
+
+    pub fn banned(mut self, reason: &str, remaining_seconds: i32) -> Self {
+        self.ban = Some((reason.to_string(), remaining_seconds));
+        self
+    }
+
+    pub fn online(mut self, online: bool) -> Self {
+        self.online = online;
+        self
+    }
+
+    pub fn in_game(mut self, in_game: bool) -> Self {
+        self.in_game = in_game;
+        self
+    }
+
+    pub fn can_join(mut self, can_join: bool) -> Self {
+        self.can_join = can_join;
+        self
+    }
+
+    pub fn party_full(mut self, party_full: bool) -> Self {
+        self.party_full = party_full;
+        self
+    }
+
+    pub fn selected_legend(mut self, legend: &str) -> Self {
+        self.selected_legend = legend.to_string();
+        self
+    }
+
+    pub fn kills(mut self, kills: i32) -> Self {
+        self.kills = kills;
+        self
+    }
+
+    /// The body of `/bridge`, shaped like [`crate::data_types::ApexUser`]
+    pub fn to_json(&self) -> Value {
+        let (current_state, state_text) = match (self.online, self.in_game) {
+            (false, _) => ("offline", "Offline"),
+            (true, false) => ("inLobby", "In lobby"),
+            (true, true) => ("inMatch", "In match"),
+        };
+        let (ban_active, ban_reason, ban_remaining) = match &self.ban {
+            Some((reason, remaining)) => (true, reason.as_str(), *remaining),
+            None => (false, "NONE", 0),
+        };
+
+        json!({
+            "global": {
+                "name": self.name,
+                "uid": self.uid,
+                "avatar": "",
+                "platform": self.platform.as_str(),
+                "level": self.level,
+                "toNextLevelPercent": self.to_next_level_percent,
+                "internalUpdateCount": 1,
+                "bans": {
+                    "isActive": ban_active,
+                    "remainingSeconds": ban_remaining,
+                    "last_banReason": ban_reason,
+                },
+                "rank": self.rank.to_json(),
+                "arena": self.arena.to_json(),
+                "battlepass": {
+                    "level": "1",
+                    "history": (1..=11)
+                        .map(|season| (format!("season{}", season), json!(0)))
+                        .collect::<serde_json::Map<_, _>>(),
+                },
+                "internalParsingVersion": 2,
+                "badges": [],
+                "levelPrestige": 0,
+            },
+            "realtime": {
+                "lobbyState": if self.party_full { "invite" } else { "open" },
+                "isOnline": self.online as i32,
+                "isInGame": self.in_game as i32,
+                "canJoin": self.can_join as i32,
+                "partyFull": self.party_full as i32,
+                "selectedLegend": self.selected_legend,
+                "currentState": current_state,
+                "currentStateSinceTimestamp": -1,
+                "currentStateAsText": state_text,
+            },
+            "total": {
+                "kills": { "name": "BR Kills", "value": self.kills },
+                "damage": { "name": "BR Damage", "value": self.damage },
+                "games_played": { "name": "Games played", "value": self.games_played },
+                "kd": { "name": "KD", "value": "-1" },
+            },
+        })
+    }
+
+    /// The body of `/nametouid`, shaped like [`crate::data_types::ApexProfile`]
+    pub fn profile_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "uid": self.uid.to_string(),
+            "pid": self.uid.to_string(),
+            "avatar": "",
+        })
+    }
+}
+
+/// A game of the match history
+#[derive(Debug, Clone)]
+pub struct MockGame {
+    pub legend: String,
+    /// `BATTLE_ROYALE` or `ARENAS`
+    pub mode: String,
+    pub length_seconds: i32,
+    pub end_timestamp: i64,
+    pub kills: i32,
+    pub damage: i32,
+    pub br_score: i32,
+    pub br_score_change: i32,
+}
+
+impl MockGame {
+    /// A Battle Royale game of 15 minutes
+    pub fn new(legend: &str, end_timestamp: i64) -> Self {
+        Self {
+            legend: legend.to_string(),
+            mode: "BATTLE_ROYALE".to_string(),
+            length_seconds: 900,
+            end_timestamp,
+            kills: 2,
+            damage: 600,
+            br_score: 4000,
+            br_score_change: 12,
+        }
+    }
+
+    pub fn arenas(mut self) -> Self {
+        self.mode = "ARENAS".to_string();
+        self
+    }
+
+    pub fn kills(mut self, kills: i32) -> Self {
+        self.kills = kills;
+        self
+    }
+
+    pub fn score(mut self, score: i32, change: i32) -> Self {
+        self.br_score = score;
+        self.br_score_change = change;
+        self
+    }
+
+    /// A game of `/games`, shaped like [`crate::data_types::ApexGame`]
+    pub fn to_json(&self, player: &MockPlayer) -> Value {
+        json!({
+            "uid": player.uid.to_string(),
+            "name": player.name,
+            "legendPlayed": self.legend,
+            "gameMode": self.mode,
+            "gameLengthSecs": self.length_seconds,
+            "gameStartTimestamp": self.end_timestamp - self.length_seconds as i64,
+            "gameEndTimestamp": self.end_timestamp,
+            "gameData": [
+                { "key": "specialEvent_kills", "value": self.kills, "name": "BR Kills" },
+                { "key": "specialEvent_damage", "value": self.damage, "name": "BR Damage" },
+            ],
+            "gameTags": [],
+            "estimatedLevelProgress": 10,
+            "BRScoreChange": self.br_score_change,
+            "BRScore": self.br_score,
+            "ArenasScoreChange": 0,
+            "ArenasScore": 0,
+            "cosmetics": {
+                "pose": "Default",
+                "skin": "Default",
+                "frame": "Default",
+                "intro": "Default",
+                "poseRarity": "Common",
+                "skinRarity": "Common",
+                "frameRarity": "Common",
+                "introRarity": "Common",
+            },
+        })
+    }
+}
+
+/// The maps of one mode: the current one started at `start` and each map lasts `duration` seconds
+#[derive(Debug, Clone)]
+pub struct MockRotation {
+    pub maps: Vec<String>,
+    pub start: i64,
+    pub duration: i64,
+}
+
+impl MockRotation {
+    /// `maps` are played in turns, starting with the first one at `start`
+    pub fn new(maps: &[&str], start: i64, duration: Duration) -> Self {
+        Self {
+            maps: maps.iter().map(|m| m.to_string()).collect(),
+            start,
+            duration: duration.as_secs().max(1) as i64,
+        }
+    }
+
+    /// The current and next map at `now`
+    fn to_json(&self, now: i64) -> Value {
+        let elapsed = (now - self.start).max(0) / self.duration;
+        let item = |n: i64| {
+            let start = self.start + n * self.duration;
+            let map = self
+                .maps
+                .get(n as usize % self.maps.len().max(1))
+                .cloned()
+                .unwrap_or_default();
+
+            json!({
+                "start": start,
+                "end": start + self.duration,
+                "readableDate_start": readable_date(start),
+                "readableDate_end": readable_date(start + self.duration),
+                "map": map,
+                "DurationInSecs": self.duration,
+                "DurationInMinutes": self.duration / 60,
+            })
+        };
+
+        let mut current = item(elapsed);
+        let remaining = self.start + (elapsed + 1) * self.duration - now;
+        current["remainingSecs"] = json!(remaining);
+        current["remainingMins"] = json!(remaining / 60);
+
+        json!({ "current": current, "next": item(elapsed + 1) })
+    }
+}
+
+/// The map rotation of every mode, shaped like [`crate::data_types::ApexMapRotation`].
+/// The current map changes as time passes.
+#[derive(Debug, Clone)]
+pub struct MockMapRotation {
+    pub battle_royale: MockRotation,
+    pub arenas: MockRotation,
+    pub ranked: (String, String),
+    pub arenas_ranked: MockRotation,
+}
+
+impl Default for MockMapRotation {
+    /// Maps that changed 10 minutes ago, 90 minutes each in Battle Royale and 15 minutes in Arenas
+    fn default() -> Self {
+        let start = unix_now() - 10 * 60;
+
+        Self {
+            battle_royale: MockRotation::new(
+                &["Olympus", "Storm Point", "World's Edge"],
+                start,
+                Duration::from_secs(90 * 60),
+            ),
+            arenas: MockRotation::new(
+                &["Phase Runner", "Party Crasher", "Encore"],
+                start,
+                Duration::from_secs(15 * 60),
+            ),
+            ranked: ("Storm Point".to_string(), "Unknown".to_string()),
+            arenas_ranked: MockRotation::new(
+                &["Overflow", "Drop Off", "Habitat 4"],
+                start,
+                Duration::from_secs(15 * 60),
+            ),
+        }
+    }
+}
+
+impl MockMapRotation {
+    pub fn to_json(&self, now: i64) -> Value {
+        json!({
+            "battle_royale": self.battle_royale.to_json(now),
+            "arenas": self.arenas.to_json(now),
+            "ranked": {
+                "current": { "map": self.ranked.0 },
+                "next": { "map": self.ranked.1 },
+            },
+            "arenasRanked": self.arenas_ranked.to_json(now),
+        })
+    }
+}
+
+/// A request received by the server
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// The path, e.g. `/bridge`
+    pub path: String,
+    /// The decoded query parameters
+    pub params: HashMap<String, String>,
+    /// The headers, with lowercase names
+    pub headers: HashMap<String, String>,
+}
+
+/// How the server reports that an API key went over the rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitStyle {
+    /// 429 Too Many Requests, with the `x-current-rate` header
+    TooManyRequests,
+    /// 200 OK with an error message, like the API often does
+    OkWithError,
+}
+
+/// Limit of requests per second for each API key
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub per_second: usize,
+    pub style: RateLimitStyle,
+}
+
+#[derive(Default)]
+struct State {
+    players: Vec<MockPlayer>,
+    games: HashMap<i64, Vec<MockGame>>,
+    rotation: MockMapRotation,
+    responses: HashMap<Endpoint, (u16, Vec<u8>)>,
+    api_keys: Option<Vec<String>>,
+    rate_limit: Option<RateLimit>,
+    current_rate: Option<String>,
+    recent: HashMap<String, VecDeque<Instant>>,
+    latency: Duration,
+    disconnect_after: Option<usize>,
+    requests: Vec<MockRequest>,
+}
+
+impl State {
+    fn player(&self, name: &str, platform: &str) -> Option<&MockPlayer> {
+        self.players
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name) && p.platform.as_str() == platform)
+    }
+
+    /// Whether `api_key` went over the rate limit, counting this request
+    fn is_rate_limited(&mut self, api_key: &str) -> bool {
+        let limit = match self.rate_limit {
+            Some(limit) => limit.per_second,
+            None => return false,
+        };
+
+        let now = Instant::now();
+        let recent = self.recent.entry(api_key.to_string()).or_default();
+        while matches!(recent.front(), Some(t) if now.duration_since(*t) >= Duration::from_secs(1))
+        {
+            recent.pop_front();
+        }
+        recent.push_back(now);
+
+        recent.len() > limit
+    }
+}
+
+/// An HTTP server answering like the API, on a random local port
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+    hits: Arc<AtomicUsize>,
+    stopped: Arc<AtomicBool>,
+}
+
+fn not_found(name: &str) -> (u16, Vec<(&'static str, String)>, Vec<u8>) {
+    let body = json!({ "Error": format!("Player {} not found", name) });
+
+    (200, Vec::new(), body.to_string().into_bytes())
+}
+
+impl MockServer {
+    /// Starts a server without players, with the default map rotation and every service up
+    ///
+    /// # Panics
+    /// If no local port is available
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not start the mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = Self {
+            url,
+            state: Arc::new(Mutex::new(State::default())),
+            hits: Arc::new(AtomicUsize::new(0)),
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+
+        let (state, hits, stopped) = (
+            server.state.clone(),
+            server.hits.clone(),
+            server.stopped.clone(),
+        );
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let (state, hits) = (state.clone(), hits.clone());
+                thread::spawn(move || handle(stream, &state, &hits));
+            }
+        });
+
+        server
+    }
+
+    /// The base URL of the server, see [`crate::client::ApexClientBuilder::base_url`]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Number of requests received so far
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    /// Every request received so far, oldest first
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state().requests.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Adds a player, or replaces the one with the same name and platform
+    pub fn add_player(&self, player: MockPlayer) {
+        let mut state = self.state();
+        state.players.retain(|p| {
+            !(p.name.eq_ignore_ascii_case(&player.name) && p.platform == player.platform)
+        });
+        state.players.push(player);
+    }
+
+    /// Removes every player with this name
+    pub fn remove_player(&self, name: &str) {
+        self.state()
+            .players
+            .retain(|p| !p.name.eq_ignore_ascii_case(name));
+    }
+
+    /// Sets the match history of the player with the given UID
+    pub fn set_games(&self, uid: i64, games: Vec<MockGame>) {
+        self.state().games.insert(uid, games);
+    }
+
+    pub fn set_map_rotation(&self, rotation: MockMapRotation) {
+        self.state().rotation = rotation;
+    }
+
+    /// Answers every request to `endpoint` with this status and body, instead of the generated data
+    pub fn set_response(&self, endpoint: Endpoint, status: u16, body: &str) {
+        self.state()
+            .responses
+            .insert(endpoint, (status, body.as_bytes().to_vec()));
+    }
+
+    /// Like [`MockServer::set_response`], for bodies that are not valid UTF-8
+    pub fn set_response_bytes(&self, endpoint: Endpoint, status: u16, body: &[u8]) {
+        self.state()
+            .responses
+            .insert(endpoint, (status, body.to_vec()));
+    }
+
+    /// Goes back to the generated data for `endpoint`
+    pub fn clear_response(&self, endpoint: Endpoint) {
+        self.state().responses.remove(&endpoint);
+    }
+
+    /// Only accepts these API keys, the others get 401 Unauthorized. Every key is accepted by default.
+    pub fn set_api_keys(&self, api_keys: &[&str]) {
+        self.state().api_keys = Some(api_keys.iter().map(|k| k.to_string()).collect());
+    }
+
+    /// Limits the requests per second of each API key. No limit by default.
+    pub fn set_rate_limit(&self, rate_limit: Option<RateLimit>) {
+        let mut state = self.state();
+        state.rate_limit = rate_limit;
+        state.recent.clear();
+    }
+
+    /// The `x-current-rate` header of the 429 responses, `1` by default.
+    /// Any value is sent as is, to test how clients handle invalid ones.
+    pub fn set_current_rate(&self, rate: &str) {
+        self.state().current_rate = Some(rate.to_string());
+    }
+
+    /// Waits this long before answering each request
+    pub fn set_latency(&self, latency: Duration) {
+        self.state().latency = latency;
+    }
+
+    /// Closes the connection after sending this many bytes of each response body,
+    /// while still announcing the full `Content-Length`. Bodies are sent whole by default.
+    pub fn set_disconnect_after(&self, bytes: Option<usize>) {
+        self.state().disconnect_after = bytes;
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wakes up the accepting thread so it sees the server was stopped
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://"));
+    }
+}
+
+fn respond(
+    state: &mut State,
+    path: &str,
+    params: &HashMap<String, String>,
+    api_key: &str,
+) -> (u16, Vec<(&'static str, String)>, Vec<u8>) {
+    let param = |name: &str| params.get(name).map(String::as_str).unwrap_or("");
+
+    if matches!(&state.api_keys, Some(keys) if !keys.iter().any(|k| k == api_key)) {
+        return (401, Vec::new(), Vec::new());
+    }
+
+    if state.is_rate_limited(api_key) {
+        let style = state.rate_limit.map(|l| l.style);
+
+        return match style {
+            Some(RateLimitStyle::OkWithError) => (
+                200,
+                Vec::new(),
+                json!({ "Error": "Slow down! You have reached the rate limit for your API key." })
+                    .to_string()
+                    .into_bytes(),
+            ),
+            _ => {
+                let rate = state
+                    .current_rate
+                    .clone()
+                    .unwrap_or_else(|| "1".to_string());
+
+                (429, vec![("x-current-rate", rate)], Vec::new())
+            }
+        };
+    }
+
+    let endpoint = match path.trim_start_matches('/') {
+        "bridge" => Endpoint::Player,
+        "games" => Endpoint::MatchHistory,
+        "nametouid" => Endpoint::NameToUid,
+        "maprotation" => Endpoint::MapRotation,
+        "servers" => Endpoint::ServerStatus,
+        "predator" => Endpoint::Predator,
+        _ => return (404, Vec::new(), Vec::new()),
+    };
+
+    if let Some((status, body)) = state.responses.get(&endpoint) {
+        return (*status, Vec::new(), body.clone());
+    }
+
+    let body = match endpoint {
+        Endpoint::Player => match state.player(param("player"), param("platform")) {
+            Some(player) => player.to_json(),
+            None => return not_found(param("player")),
+        },
+        Endpoint::NameToUid => match state.player(param("player"), param("platform")) {
+            Some(player) => player.profile_json(),
+            None => return not_found(param("player")),
+        },
+        Endpoint::MatchHistory => {
+            let player = match state
+                .players
+                .iter()
+                .find(|p| p.uid.to_string() == param("uid"))
+            {
+                Some(player) => player,
+                None => return not_found(param("uid")),
+            };
+            let start: i64 = param("start").parse().unwrap_or(i64::MIN);
+            let end: i64 = param("end").parse().unwrap_or(i64::MAX);
+            let limit: usize = param("limit").parse().unwrap_or(usize::MAX);
+
+            let mut games: Vec<&MockGame> = state
+                .games
+                .get(&player.uid)
+                .map(|games| games.iter().collect())
+                .unwrap_or_default();
+            games.sort_by_key(|g| std::cmp::Reverse(g.end_timestamp));
+
+            Value::Array(
+                games
+                    .into_iter()
+                    .filter(|g| g.end_timestamp >= start && g.end_timestamp <= end)
+                    .filter(|g| param("mode").is_empty() || g.mode == param("mode"))
+                    .take(limit)
+                    .map(|g| g.to_json(player))
+                    .collect(),
+            )
+        }
+        Endpoint::MapRotation => state.rotation.to_json(unix_now()),
+        Endpoint::ServerStatus => server_status(),
+        Endpoint::Predator => predator(),
+    };
+
+    (200, Vec::new(), body.to_string().into_bytes())
+}
+
+/// Every service up in every region
+fn server_status() -> Value {
+    let now = unix_now();
+    let up = json!({
+        "Status": "UP",
+        "HTTPCode": 200,
+        "ResponseTime": 80,
+        "QueryTimestamp": now,
+    });
+    let regions: serde_json::Map<String, Value> = Region::ALL
+        .iter()
+        .map(|region| (region.to_string(), up.clone()))
+        .collect();
+
+    json!({
+        "Origin_login": regions,
+        "EA_novafusion": regions,
+        "EA_accounts": regions,
+        "ApexOauth_Crossplay": regions,
+    })
+}
+
+fn predator() -> Value {
+    let now = unix_now();
+    let platforms = |base: i32| {
+        json!({
+            "PC": { "foundRank": 750, "val": base, "uid": "1", "updateTimestamp": now, "totalMastersAndPreds": 5000 },
+            "PS4": { "foundRank": 750, "val": base - 800, "uid": "2", "updateTimestamp": now, "totalMastersAndPreds": 3500 },
+            "X1": { "foundRank": 750, "val": base - 2000, "uid": "3", "updateTimestamp": now, "totalMastersAndPreds": 1200 },
+            "SWITCH": { "foundRank": 750, "val": base - 4000, "uid": "4", "updateTimestamp": now, "totalMastersAndPreds": 300 },
+        })
+    };
+
+    json!({ "RP": platforms(15000), "AP": platforms(9500) })
+}
+
+fn handle(mut stream: TcpStream, state: &Mutex<State>, hits: &AtomicUsize) {
+    let mut reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(_) => return,
+    };
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() || request_line.is_empty() {
+        return;
+    }
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).is_err() || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    // Requests sent through a proxy carry the whole URL
+    let target = match target.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |start| &rest[start..]),
+        None => target,
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params: HashMap<String, String> = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+
+    hits.fetch_add(1, Ordering::SeqCst);
+    let api_key = headers.get("authorization").cloned().unwrap_or_default();
+
+    let latency = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state.requests.push(MockRequest {
+            path: path.to_string(),
+            params: params.clone(),
+            headers,
+        });
+        state.latency
+    };
+    thread::sleep(latency);
+
+    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+    let (status, headers, body) = respond(&mut state, path, &params, &api_key);
+    let sent = state.disconnect_after.unwrap_or(body.len()).min(body.len());
+    drop(state);
+
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reqwest::StatusCode::from_u16(status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or(""),
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+
+    let _ = stream
+        .write_all(response.as_bytes())
+        .and_then(|_| stream.write_all(&body[..sent]));
+}
@@ -0,0 +1,87 @@
+use std::{convert::TryFrom, fmt};
+
+/// Longest name accepted, in characters. Steam allows the longest names of all the platforms.
+pub const MAX_PLAYER_NAME_LENGTH: usize = 32;
+
+/// Reason why a player name was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerNameError {
+    /// The name is empty or only contains whitespace
+    Empty,
+    /// The name is longer than [`MAX_PLAYER_NAME_LENGTH`] characters
+    TooLong(usize),
+    /// The name contains a control character
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for PlayerNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerNameError::Empty => f.write_str("the name is empty"),
+            PlayerNameError::TooLong(length) => write!(
+                f,
+                "the name is {} characters long, the maximum is {}",
+                length, MAX_PLAYER_NAME_LENGTH
+            ),
+            PlayerNameError::InvalidCharacter(c) => {
+                write!(f, "the name contains the invalid character {:?}", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlayerNameError {}
+
+/// A player name that can be safely sent to the API.
+/// Surrounding whitespace is removed, every other character is URL encoded when sending the request.
+///
+/// These are the only rules: a name is rejected if it is empty, longer than [`MAX_PLAYER_NAME_LENGTH`]
+/// characters or contains a control character. Any other character is allowed, including spaces,
+/// punctuation such as `&`, `#` or `?` and non-ASCII letters, since the platforms allow them in names.
+///
+/// # Examples
+/// ```
+/// use apex_legends_api::player_name::{PlayerName, PlayerNameError};
+///
+/// assert_eq!(PlayerName::new(" Rock & Roll ").unwrap().as_str(), "Rock & Roll");
+/// assert_eq!(PlayerName::new("  "), Err(PlayerNameError::Empty));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerName(String);
+
+impl PlayerName {
+    pub fn new(name: &str) -> Result<Self, PlayerNameError> {
+        let name = name.trim();
+        let length = name.chars().count();
+
+        if length == 0 {
+            return Err(PlayerNameError::Empty);
+        }
+        if length > MAX_PLAYER_NAME_LENGTH {
+            return Err(PlayerNameError::TooLong(length));
+        }
+        if let Some(c) = name.chars().find(|c| c.is_control()) {
+            return Err(PlayerNameError::InvalidCharacter(c));
+        }
+
+        Ok(Self(name.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<&str> for PlayerName {
+    type Error = PlayerNameError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        Self::new(name)
+    }
+}
+
+impl fmt::Display for PlayerName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,225 @@
+//! Building requests and interpreting responses, shared by the async and blocking clients
+
+use std::{sync::Arc, time::Duration};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    StatusCode,
+};
+use serde::de::DeserializeOwned;
+use url::form_urlencoded;
+
+use crate::{
+    client::Endpoint,
+    data_types::{self, Platform},
+    games::GamesQuery,
+    get_rate,
+    keys::{KeyPool, NoKey},
+    player_name::PlayerName,
+    transport::{self, Response},
+    Error,
+};
+
+pub(crate) const BASE_URL: &str = "https://api.mozambiquehe.re";
+
+pub(crate) const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// A request to the API, without the API key
+#[derive(Clone)]
+pub(crate) struct Request {
+    pub(crate) endpoint: Endpoint,
+    params: Vec<(&'static str, String)>,
+}
+
+impl Request {
+    pub(crate) fn user(username: &PlayerName, platform: Platform) -> Self {
+        Self {
+            endpoint: Endpoint::Player,
+            params: vec![
+                ("version", "5".to_string()),
+                ("platform", platform.to_string()),
+                ("player", username.to_string()),
+            ],
+        }
+    }
+
+    pub(crate) fn games(query: &GamesQuery) -> Self {
+        Self {
+            endpoint: Endpoint::MatchHistory,
+            params: query.params(),
+        }
+    }
+
+    pub(crate) fn name_to_uid(username: &PlayerName, platform: Platform) -> Self {
+        Self {
+            endpoint: Endpoint::NameToUid,
+            params: vec![
+                ("player", username.to_string()),
+                ("platform", platform.to_string()),
+            ],
+        }
+    }
+
+    pub(crate) fn map_rotation() -> Self {
+        Self {
+            endpoint: Endpoint::MapRotation,
+            params: vec![("version", "2".to_string())],
+        }
+    }
+
+    pub(crate) fn server_status() -> Self {
+        Self {
+            endpoint: Endpoint::ServerStatus,
+            params: Vec::new(),
+        }
+    }
+
+    pub(crate) fn predator() -> Self {
+        Self {
+            endpoint: Endpoint::Predator,
+            params: Vec::new(),
+        }
+    }
+
+    /// The URL encoded query string
+    fn query(&self) -> String {
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(&self.params)
+            .finish()
+    }
+
+    /// Identifies the request in the cache and among the requests in flight
+    pub(crate) fn key(&self) -> String {
+        format!("{}?{}", self.endpoint.path(), self.query())
+    }
+
+    pub(crate) fn url(&self, base_url: &str) -> String {
+        format!(
+            "{}/{}?{}",
+            base_url.trim_end_matches('/'),
+            self.endpoint.path(),
+            self.query()
+        )
+    }
+
+    /// The request sent with the key in `auth`, on top of the `headers` sent with every request
+    pub(crate) fn to_transport(
+        &self,
+        base_url: &str,
+        headers: &HeaderMap,
+        auth: HeaderValue,
+    ) -> transport::Request {
+        let mut headers = headers.clone();
+        headers.insert(AUTHORIZATION, auth);
+
+        transport::Request {
+            url: self.url(base_url),
+            headers,
+        }
+    }
+}
+
+/// Parses a response body.
+/// The inner error is the one reported by the API (e.g. the player was not found) if the body does not match `T`
+pub(crate) fn parse<T: DeserializeOwned>(body: &str) -> Result<Result<T, Error>, Error> {
+    match serde_json::from_str(body) {
+        Ok(data) => Ok(Ok(data)),
+        Err(e) => match serde_json::from_str::<data_types::ApexError>(body) {
+            Ok(err) => Ok(Err(Error::Api(err.message))),
+            Err(_) => Err(Error::Parse(e.to_string())),
+        },
+    }
+}
+
+/// Whether an error is the API reporting that the player does not exist, e.g. `Player HeyImNobody not found`
+pub(crate) fn is_player_not_found(error: &Error) -> bool {
+    matches!(error, Error::Api(message) if message.to_lowercase().contains("not found"))
+}
+
+/// Whether a 200 OK response is actually the API reporting that the rate limit was hit
+fn is_rate_limited(body: &str) -> bool {
+    matches!(
+        serde_json::from_str::<data_types::ApexError>(body),
+        Ok(err) if err.message.to_lowercase().contains("rate limit")
+    )
+}
+
+/// What to do next while sending a request
+pub(crate) enum Attempt {
+    /// Send the request with the key `id`, in the `Authorization` header
+    Send(usize, HeaderValue),
+    /// Every key is rate limited, wait before trying again
+    Wait(Duration),
+}
+
+/// Picks the API key of each attempt at sending a request.
+///
+/// A key that hits the rate limit is benched for the time given by the `x-current-rate` header or DEFAULT_RATE,
+/// and the request is sent again (once per key) with another key that is not benched.
+/// When every key is benched, the request waits for the first one to be usable again if `retry` is enabled,
+/// and fails with 429 otherwise.
+/// A key rejected by the API is dropped and the request is sent again with another key.
+///
+/// The API key is sent in the `Authorization` header so it never shows up in the URL of an error.
+pub(crate) struct Attempts {
+    keys: Arc<KeyPool>,
+    retry: bool,
+    retries: usize,
+}
+
+impl Attempts {
+    pub(crate) fn new(keys: Arc<KeyPool>, retry: bool) -> Self {
+        let retries = keys.len();
+
+        Self {
+            keys,
+            retry,
+            retries,
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Result<Attempt, Error> {
+        match self.keys.next_key() {
+            Ok((id, api_key)) => {
+                let mut auth = HeaderValue::from_str(&api_key).map_err(|_| Error::InvalidApiKey)?;
+                auth.set_sensitive(true);
+
+                Ok(Attempt::Send(id, auth))
+            }
+            Err(NoKey::AllRevoked) => Err(Error::Status(StatusCode::UNAUTHORIZED)),
+            Err(NoKey::AllBenched(_)) if !self.retry => {
+                Err(Error::Status(StatusCode::TOO_MANY_REQUESTS))
+            }
+            Err(NoKey::AllBenched(wait)) => Ok(Attempt::Wait(wait)),
+        }
+    }
+
+    /// Handles the response to the attempt with the key `id`.
+    /// Returns `None` if the request must be sent again.
+    pub(crate) fn finish(
+        &mut self,
+        id: usize,
+        response: Response,
+    ) -> Option<Result<String, Error>> {
+        let rate = match response.status {
+            StatusCode::TOO_MANY_REQUESTS => get_rate(Some(response.headers)),
+            StatusCode::UNAUTHORIZED => {
+                self.keys.revoke(id);
+                return None;
+            }
+            status if !status.is_success() => return Some(Err(Error::Status(status))),
+            _ if is_rate_limited(&response.body) => get_rate(None),
+            _ => return Some(Ok(response.body)),
+        };
+
+        self.keys.bench(id, Duration::from_secs_f32(rate));
+
+        if self.retries == 0 {
+            return Some(Err(Error::Status(StatusCode::TOO_MANY_REQUESTS)));
+        }
+        self.retries -= 1;
+
+        None
+    }
+}
@@ -0,0 +1,46 @@
+//! The parts of the async runtime used by the client, picked by the `runtime-tokio` or `runtime-async-std` feature.
+//! When both are enabled, tokio is used.
+
+use std::{future::Future, time::Duration};
+
+/// Waits for `duration` without blocking the thread
+#[cfg(feature = "runtime-tokio")]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_io::Timer::after(duration).await;
+}
+
+/// Returns `None` if `future` does not complete within `duration`
+#[cfg(feature = "runtime-tokio")]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    tokio::time::timeout(duration, future).await.ok()
+}
+
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    use futures::future::{select, Either};
+
+    futures::pin_mut!(future);
+
+    match select(future, async_io::Timer::after(duration)).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+/// Runs a future of the HTTP client, which needs to be polled inside a tokio runtime
+#[cfg(feature = "runtime-tokio")]
+pub(crate) fn compat<F: Future>(future: F) -> F {
+    future
+}
+
+/// Runs a future of the HTTP client, which needs to be polled inside a tokio runtime.
+/// Outside of one, it is polled in a small tokio runtime running in the background.
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+pub(crate) fn compat<F: Future>(future: F) -> async_compat::Compat<F> {
+    async_compat::Compat::new(future)
+}
@@ -0,0 +1,286 @@
+//! What changed for a player during a play session: ranked points, promotions and levels.
+//!
+//! # Examples
+//! ```no_run
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! use apex_legends_api::{session::SessionTracker, ApexClient};
+//!
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! async fn session() -> Result<(), apex_legends_api::Error> {
+//!     let client = ApexClient::new("your_api_key");
+//!     let mut session = SessionTracker::new(&client.get_user("HeyImLifeline").await?);
+//!
+//!     // After each game
+//!     for change in session.record(&client.get_user("HeyImLifeline").await?) {
+//!         println!("{:?}", change);
+//!     }
+//!
+//!     let summary = session.summary();
+//!     println!("{:+} RP, {} levels", summary.battle_royale.points, summary.levels);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::{cmp::Ordering, fmt};
+
+use crate::{
+    data_types::{ApexRank, ApexUser},
+    games::GameMode,
+};
+
+/// The tiers, from the lowest to the highest
+const TIERS: [&str; 9] = [
+    "Unranked",
+    "Rookie",
+    "Bronze",
+    "Silver",
+    "Gold",
+    "Platinum",
+    "Diamond",
+    "Master",
+    "Apex Predator",
+];
+
+/// A tier and division, e.g. Diamond 2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tier {
+    pub name: String,
+    /// From 4, the lowest, to 1. 0 for the tiers without divisions, like Master
+    pub division: i32,
+}
+
+impl Tier {
+    pub fn new(rank: &ApexRank) -> Self {
+        Self {
+            name: rank.rank_name.clone(),
+            division: rank.rank_division,
+        }
+    }
+
+    /// Compares two tiers. `None` if one of them is unknown
+    pub fn compare(&self, other: &Tier) -> Option<Ordering> {
+        let position = |tier: &Tier| {
+            let index = TIERS
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(&tier.name))?;
+
+            Some((index, -tier.division))
+        };
+
+        Some(position(self)?.cmp(&position(other)?))
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.division {
+            0 => f.write_str(&self.name),
+            division => write!(f, "{} {}", self.name, division),
+        }
+    }
+}
+
+/// A change between two snapshots of a player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionChange {
+    /// Ranked points gained, or lost if `change` is negative. `score` is the new total
+    Points {
+        mode: GameMode,
+        change: i32,
+        score: i32,
+    },
+    Promoted {
+        mode: GameMode,
+        from: Tier,
+        to: Tier,
+    },
+    Demoted {
+        mode: GameMode,
+        from: Tier,
+        to: Tier,
+    },
+    LevelUp {
+        from: i32,
+        to: i32,
+    },
+}
+
+/// What changed in a ranked mode since the start of the session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeSummary {
+    /// The tier at the start of the session
+    pub start: Tier,
+    pub current: Tier,
+    /// Points gained since the start, negative if lost
+    pub points: i32,
+    /// The current points
+    pub score: i32,
+    pub promotions: u32,
+    pub demotions: u32,
+}
+
+/// What changed since the start of the session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub battle_royale: ModeSummary,
+    pub arenas: ModeSummary,
+    /// Levels gained
+    pub levels: i32,
+    /// Progress since the start in percent of a level, e.g. 150 for a level and a half
+    pub level_progress: i32,
+    /// The number of snapshots recorded, including the first one
+    pub snapshots: usize,
+}
+
+/// The parts of [`ApexUser`] that are tracked
+#[derive(Debug, Clone)]
+struct Snapshot {
+    level: i32,
+    to_next_level_percent: i32,
+    battle_royale: (Tier, i32),
+    arenas: (Tier, i32),
+}
+
+impl Snapshot {
+    fn new(user: &ApexUser) -> Self {
+        let global = &user.global;
+
+        Self {
+            level: global.level,
+            to_next_level_percent: global.to_next_level_percent,
+            battle_royale: (Tier::new(&global.rank), global.rank.rank_score),
+            arenas: (Tier::new(&global.arena), global.arena.rank_score),
+        }
+    }
+
+    fn mode(&self, mode: GameMode) -> &(Tier, i32) {
+        match mode {
+            GameMode::BattleRoyale => &self.battle_royale,
+            GameMode::Arenas => &self.arenas,
+        }
+    }
+
+    /// The level progress in percent of a level
+    fn progress(&self) -> i32 {
+        self.level * 100 + self.to_next_level_percent
+    }
+
+    /// The changes from `self` to `now`
+    fn changes(&self, now: &Snapshot) -> Vec<SessionChange> {
+        let mut changes = Vec::new();
+
+        for mode in [GameMode::BattleRoyale, GameMode::Arenas] {
+            let ((before, before_score), (after, after_score)) = (self.mode(mode), now.mode(mode));
+
+            if after_score != before_score {
+                changes.push(SessionChange::Points {
+                    mode,
+                    change: after_score - before_score,
+                    score: *after_score,
+                });
+            }
+            match after.compare(before) {
+                Some(Ordering::Greater) => changes.push(SessionChange::Promoted {
+                    mode,
+                    from: before.clone(),
+                    to: after.clone(),
+                }),
+                Some(Ordering::Less) => changes.push(SessionChange::Demoted {
+                    mode,
+                    from: before.clone(),
+                    to: after.clone(),
+                }),
+                _ => {}
+            }
+        }
+        if now.level > self.level {
+            changes.push(SessionChange::LevelUp {
+                from: self.level,
+                to: now.level,
+            });
+        }
+
+        changes
+    }
+}
+
+/// The index of the counters of `mode` in [`SessionTracker`]
+fn index(mode: GameMode) -> usize {
+    match mode {
+        GameMode::BattleRoyale => 0,
+        GameMode::Arenas => 1,
+    }
+}
+
+/// Records successive snapshots of a player, e.g. after each game,
+/// and reports the ranked points, promotions, demotions and levels gained.
+pub struct SessionTracker {
+    start: Snapshot,
+    last: Snapshot,
+    snapshots: usize,
+    /// Promotions and demotions of Battle Royale, then Arenas
+    promotions: [u32; 2],
+    demotions: [u32; 2],
+}
+
+impl SessionTracker {
+    /// Starts a session
+    ///
+    /// # Parameters
+    /// * `user` - The player at the start of the session
+    pub fn new(user: &ApexUser) -> Self {
+        let snapshot = Snapshot::new(user);
+
+        Self {
+            start: snapshot.clone(),
+            last: snapshot,
+            snapshots: 1,
+            promotions: [0; 2],
+            demotions: [0; 2],
+        }
+    }
+
+    /// Records a new snapshot of the same player, and returns what changed since the previous one
+    pub fn record(&mut self, user: &ApexUser) -> Vec<SessionChange> {
+        let snapshot = Snapshot::new(user);
+        let changes = self.last.changes(&snapshot);
+
+        for change in &changes {
+            match change {
+                SessionChange::Promoted { mode, .. } => self.promotions[index(*mode)] += 1,
+                SessionChange::Demoted { mode, .. } => self.demotions[index(*mode)] += 1,
+                _ => {}
+            }
+        }
+        self.last = snapshot;
+        self.snapshots += 1;
+
+        changes
+    }
+
+    /// What changed since the start of the session
+    pub fn summary(&self) -> SessionSummary {
+        let mode = |mode: GameMode| {
+            let ((start, start_score), (current, score)) =
+                (self.start.mode(mode), self.last.mode(mode));
+
+            ModeSummary {
+                start: start.clone(),
+                current: current.clone(),
+                points: score - start_score,
+                score: *score,
+                promotions: self.promotions[index(mode)],
+                demotions: self.demotions[index(mode)],
+            }
+        };
+
+        SessionSummary {
+            battle_royale: mode(GameMode::BattleRoyale),
+            arenas: mode(GameMode::Arenas),
+            levels: self.last.level - self.start.level,
+            level_progress: self.last.progress() - self.start.progress(),
+            snapshots: self.snapshots,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! The HTTP layer of [`crate::ApexClient`].
+//!
+//! The client sends its requests through a [`Transport`], [`ReqwestTransport`] by default.
+//! Implement it to use another HTTP stack, or to answer the requests without any network:
+//!
+//! ```
+//! use apex_legends_api::{
+//!     transport::{HeaderMap, Request, Response, StatusCode, Transport},
+//!     Error,
+//! };
+//! use futures::future::{self, BoxFuture};
+//!
+//! /// Answers every request with the same profile
+//! struct Canned;
+//!
+//! impl Transport for Canned {
+//!     fn send(&self, _request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+//!         Box::pin(future::ready(Ok(Response {
+//!             status: StatusCode::OK,
+//!             headers: HeaderMap::new(),
+//!             body: r#"{"name": "HeyImLifeline", "uid": "1", "pid": "1", "avatar": ""}"#.to_string(),
+//!         })))
+//!     }
+//! }
+//!
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! let client = apex_legends_api::ApexClient::builder("your_api_key").build_with_transport(Canned);
+//! ```
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+
+use crate::Error;
+
+cfg_runtime! {
+    use std::time::Duration;
+
+    use futures::future::FutureExt;
+
+    use crate::{http, rt};
+}
+
+pub use reqwest::{header::HeaderMap, StatusCode};
+
+/// A GET request to the API
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// The full URL, with the query string
+    pub url: String,
+    /// Every header to send, including the `Authorization` header holding the API key.
+    /// It is marked as sensitive, never log it.
+    pub headers: HeaderMap,
+}
+
+/// The response to a [`Request`]
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The body only needs to be read for successful responses
+    pub body: String,
+}
+
+/// Sends requests to the API.
+///
+/// Failures to get a response should be reported with `Error::Request`, `Error::Timeout`, `Error::Body`,
+/// `Error::InvalidUtf8` or `Error::ResponseTooLarge`, and HTTP errors with the status of the [`Response`].
+pub trait Transport: Send + Sync + 'static {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        (**self).send(request)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        (**self).send(request)
+    }
+}
+
+cfg_runtime! {
+    /// Sends the requests with [`reqwest`]
+    #[derive(Debug, Clone)]
+    pub struct ReqwestTransport {
+        client: reqwest::Client,
+        read_timeout: Option<Duration>,
+        max_response_size: Option<usize>,
+    }
+
+    impl ReqwestTransport {
+        pub fn new(client: reqwest::Client) -> Self {
+            Self {
+                client,
+                read_timeout: None,
+                max_response_size: None,
+            }
+        }
+
+        /// Maximum time to wait for the response headers, and then for the body. No limit by default.
+        pub fn read_timeout(mut self, timeout: Duration) -> Self {
+            self.read_timeout = Some(timeout);
+            self
+        }
+
+        /// Largest response body accepted, in bytes. Bigger responses fail with `Error::ResponseTooLarge`.
+        /// No limit by default.
+        pub fn max_response_size(mut self, bytes: usize) -> Self {
+            self.max_response_size = Some(bytes);
+            self
+        }
+    }
+
+    impl Transport for ReqwestTransport {
+        fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+            rt::compat(http::get_request(
+                &self.client,
+                request,
+                self.read_timeout,
+                self.max_response_size,
+            ))
+            .boxed()
+        }
+    }
+}
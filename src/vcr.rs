@@ -0,0 +1,277 @@
+//! Record and replay the traffic of [`crate::ApexClient`].
+//!
+//! A [`Recorder`] sends the requests through another transport and saves every request and response
+//! in a cassette, a JSON file. A [`Replayer`] answers with the responses of a cassette, without any network.
+//! The API key is never saved.
+//!
+//! Record the response that breaks something once, then replay it in a regression test:
+//!
+//! ```no_run
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! use apex_legends_api::ApexClient;
+//!
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! async fn record() -> Result<(), apex_legends_api::Error> {
+//!     let client = ApexClient::builder("your_api_key").build_recording("tests/cassettes/user.json")?;
+//!     client.get_user("HeyImLifeline").await?;
+//!     // Also saved when the last clone of the client is dropped
+//!     client.save_cassette()?;
+//!
+//!     Ok(())
+//! }
+//!
+//! # #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+//! async fn replay() -> Result<(), apex_legends_api::Error> {
+//!     let client = ApexClient::builder("any_key").build_replaying("tests/cassettes/user.json")?;
+//!     client.get_user("HeyImLifeline").await?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use futures::future::{self, BoxFuture, FutureExt};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    transport::{Request, Response, Transport},
+    write_atomic, Error,
+};
+
+/// The recorded traffic
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+/// A request and the response it got
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedRequest {
+    /// The path and query string of the URL, e.g. `/bridge?version=5&platform=PC&player=HeyImLifeline`.
+    /// The root of the API is left out, so a cassette can be replayed whatever the base URL is.
+    pub path: String,
+    /// The headers sent, without the API key nor any other sensitive header
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// The path and query string of a URL
+fn path_of(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(url) => match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+/// Keeps the headers that can be saved
+fn record_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, value)| **name != AUTHORIZATION && !value.is_sensitive())
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+impl RecordedRequest {
+    pub fn new(request: &Request) -> Self {
+        Self {
+            path: path_of(&request.url),
+            headers: record_headers(&request.headers),
+        }
+    }
+
+    /// Whether this is the recording of `request`
+    pub fn matches(&self, request: &Request) -> bool {
+        self.path == path_of(&request.url)
+    }
+}
+
+impl RecordedResponse {
+    pub fn new(response: &Response) -> Self {
+        Self {
+            status: response.status.as_u16(),
+            headers: record_headers(&response.headers),
+            body: response.body.clone(),
+        }
+    }
+
+    /// The response to hand back to the client
+    pub fn to_response(&self) -> Result<Response, Error> {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|_| Error::Cassette(format!("Invalid status code {}", self.status)))?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+
+        Ok(Response {
+            status,
+            headers,
+            body: self.body.clone(),
+        })
+    }
+}
+
+impl Cassette {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Cassette(format!("{}: {}", path.display(), e)))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| Error::Cassette(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Saves the cassette, creating its directory if needed
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Cassette(format!("{}: {}", path.display(), e)))?;
+
+        write_atomic(path, content.as_bytes())
+            .map_err(|e| Error::Cassette(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Sends the requests through `T` and saves every request and response in a cassette.
+/// The traffic is kept in memory and written by [`Recorder::save`], and when the recorder is dropped,
+/// replacing the previous file. Requests that fail without a response (e.g. timeouts) are not recorded.
+pub struct Recorder<T> {
+    inner: T,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl<T: Transport> Recorder<T> {
+    pub fn new<P: Into<PathBuf>>(inner: T, path: P) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            cassette: Mutex::new(Cassette::default()),
+        }
+    }
+
+    /// The traffic recorded so far
+    pub fn cassette(&self) -> Cassette {
+        self.cassette
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Writes the traffic recorded so far to the cassette file
+    pub fn save(&self) -> Result<(), Error> {
+        self.cassette().save(&self.path)
+    }
+}
+
+impl<T> Drop for Recorder<T> {
+    fn drop(&mut self) {
+        let cassette = self.cassette.get_mut().unwrap_or_else(|e| e.into_inner());
+        let _ = cassette.save(&self.path);
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        async move {
+            let recorded = RecordedRequest::new(&request);
+            let response = self.inner.send(request).await?;
+
+            self.cassette
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .interactions
+                .push(Interaction {
+                    request: recorded,
+                    response: RecordedResponse::new(&response),
+                });
+
+            Ok(response)
+        }
+        .boxed()
+    }
+}
+
+/// Answers with the responses of a cassette.
+///
+/// Each request gets the first response recorded for the same path and query string that was not replayed yet,
+/// and the last one again once they have all been replayed.
+/// Requests that were never recorded fail with `Error::Cassette`.
+pub struct Replayer {
+    interactions: Vec<Interaction>,
+    replayed: Mutex<Vec<bool>>,
+}
+
+impl Replayer {
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            replayed: Mutex::new(vec![false; cassette.interactions.len()]),
+            interactions: cassette.interactions,
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    fn replay(&self, request: &Request) -> Result<Response, Error> {
+        let mut replayed = self.replayed.lock().unwrap_or_else(|e| e.into_inner());
+
+        let matching: Vec<usize> = (0..self.interactions.len())
+            .filter(|&i| self.interactions[i].request.matches(request))
+            .collect();
+
+        let index = matching
+            .iter()
+            .copied()
+            .find(|&i| !replayed[i])
+            .or_else(|| matching.last().copied())
+            .ok_or_else(|| {
+                Error::Cassette(format!(
+                    "No response recorded for {}",
+                    path_of(&request.url)
+                ))
+            })?;
+
+        replayed[index] = true;
+        self.interactions[index].response.to_response()
+    }
+}
+
+impl Transport for Replayer {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        future::ready(self.replay(&request)).boxed()
+    }
+}
@@ -0,0 +1,511 @@
+//! Streams of events about the game, built by fetching the API only when something can have changed.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures::stream::{self, Stream};
+
+use crate::{
+    client::ApexClient,
+    data_types::{ApexMapRotation, ApexRealtime, Platform},
+    rt,
+    transport::{ReqwestTransport, Transport},
+    Error,
+};
+
+/// A mode of the map rotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RotationMode {
+    BattleRoyale,
+    Ranked,
+    Arenas,
+    ArenasRanked,
+}
+
+impl RotationMode {
+    /// Every mode of the map rotation
+    pub const ALL: [RotationMode; 4] = [
+        RotationMode::BattleRoyale,
+        RotationMode::Ranked,
+        RotationMode::Arenas,
+        RotationMode::ArenasRanked,
+    ];
+
+    /// The name of the mode, e.g. `Ranked Arenas`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RotationMode::BattleRoyale => "Battle Royale",
+            RotationMode::Ranked => "Ranked",
+            RotationMode::Arenas => "Arenas",
+            RotationMode::ArenasRanked => "Ranked Arenas",
+        }
+    }
+}
+
+impl fmt::Display for RotationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The current map of a mode changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapChange {
+    pub mode: RotationMode,
+    /// The map that was current before
+    pub previous: String,
+    /// The new current map
+    pub map: String,
+    /// Unix timestamp of the end of the new map.
+    /// `None` in ranked Battle Royale, where the map only changes with the splits.
+    pub end: Option<i64>,
+    /// The map after the new one
+    pub next: String,
+}
+
+/// An event of a [`MapRotationWatcher`]
+#[derive(Debug)]
+pub enum MapRotationEvent {
+    /// The rotation when the watcher started
+    Started(Box<ApexMapRotation>),
+    /// The current map of a mode changed. When several modes change at once, there is one event per mode.
+    Changed(MapChange),
+}
+
+/// The current map, its end and the next map of every mode
+fn current_maps(rotation: &ApexMapRotation) -> Vec<MapChange> {
+    RotationMode::ALL
+        .iter()
+        .map(|&mode| {
+            let (map, end, next) = match mode {
+                RotationMode::BattleRoyale => (
+                    &rotation.battle_royale.current.map,
+                    Some(rotation.battle_royale.current.end),
+                    &rotation.battle_royale.next.map,
+                ),
+                RotationMode::Ranked => (
+                    &rotation.ranked.current.map,
+                    None,
+                    &rotation.ranked.next.map,
+                ),
+                RotationMode::Arenas => (
+                    &rotation.arenas.current.map,
+                    Some(rotation.arenas.current.end),
+                    &rotation.arenas.next.map,
+                ),
+                RotationMode::ArenasRanked => (
+                    &rotation.arenas_ranked.current.map,
+                    Some(rotation.arenas_ranked.current.end),
+                    &rotation.arenas_ranked.next.map,
+                ),
+            };
+
+            MapChange {
+                mode,
+                previous: String::new(),
+                map: map.clone(),
+                end,
+                next: next.clone(),
+            }
+        })
+        .collect()
+}
+
+fn since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Yields an event whenever the current map of a mode changes.
+///
+/// Instead of polling, the rotation is fetched again `delay` after the end of the first current map to end.
+/// If the API still returns a map that should have ended, or if the request fails, it is fetched again after `retry_delay`.
+///
+/// # Examples
+/// ```no_run
+/// use apex_legends_api::{
+///     watch::{MapRotationEvent, MapRotationWatcher},
+///     ApexClient,
+/// };
+/// use futures::StreamExt;
+///
+/// async fn announce() {
+///     let client = ApexClient::new("your_api_key");
+///     let mut events = Box::pin(MapRotationWatcher::new(client).stream());
+///
+///     while let Some(event) = events.next().await {
+///         match event {
+///             Ok(MapRotationEvent::Changed(change)) => {
+///                 println!("{} is now on {}", change.mode, change.map)
+///             }
+///             Ok(MapRotationEvent::Started(_)) => {}
+///             Err(e) => println!("There was an error!: {}", e),
+///         }
+///     }
+/// }
+/// ```
+pub struct MapRotationWatcher<T = ReqwestTransport> {
+    client: ApexClient<T>,
+    delay: Duration,
+    retry_delay: Duration,
+}
+
+impl<T: Transport> MapRotationWatcher<T> {
+    pub fn new(client: ApexClient<T>) -> Self {
+        Self {
+            client,
+            delay: Duration::from_secs(2),
+            retry_delay: Duration::from_secs(10),
+        }
+    }
+
+    /// How long after the end of a map to fetch the rotation again, 2 seconds by default.
+    /// The API may take a moment to switch to the next map.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// How long to wait before fetching the rotation again after an error or an outdated rotation,
+    /// 10 seconds by default
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// The events, starting with [`MapRotationEvent::Started`].
+    /// Errors are yielded too, and the stream goes on with another attempt. It never ends.
+    pub fn stream(self) -> impl Stream<Item = Result<MapRotationEvent, Error>> {
+        let state = WatchState {
+            watcher: self,
+            current: None,
+            events: VecDeque::new(),
+            wait: None,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.events.pop_front() {
+                    return Some((Ok(event), state));
+                }
+                if let Some(wait) = state.wait.take() {
+                    rt::sleep(wait).await;
+                }
+
+                match state.watcher.client.get_map_rotation().await {
+                    Ok(rotation) => state.push(rotation),
+                    Err(e) => {
+                        state.wait = Some(state.watcher.retry_delay);
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+}
+
+struct WatchState<T> {
+    watcher: MapRotationWatcher<T>,
+    /// The maps of the last rotation fetched
+    current: Option<Vec<MapChange>>,
+    events: VecDeque<MapRotationEvent>,
+    /// How long to wait before the next request
+    wait: Option<Duration>,
+}
+
+impl<T> WatchState<T> {
+    /// Queues the changes since the last rotation, and schedules the next request
+    fn push(&mut self, rotation: ApexMapRotation) {
+        let now = since_epoch();
+        let next_change = Duration::from_secs(rotation.next_change().max(0) as u64);
+        self.wait = Some(if next_change > now {
+            next_change + self.watcher.delay - now
+        } else {
+            self.watcher.retry_delay
+        });
+
+        let maps = current_maps(&rotation);
+        match self.current.replace(maps.clone()) {
+            None => self
+                .events
+                .push_back(MapRotationEvent::Started(Box::new(rotation))),
+            Some(previous) => {
+                for (before, after) in previous.into_iter().zip(maps) {
+                    if before.map != after.map {
+                        self.events.push_back(MapRotationEvent::Changed(MapChange {
+                            previous: before.map,
+                            ..after
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What changed in the presence of a player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresenceChange {
+    CameOnline,
+    WentOffline,
+    EnteredMatch,
+    LeftMatch,
+    ChangedLegend {
+        previous: String,
+        legend: String,
+    },
+    /// The player is online in a party that can be joined and is not full
+    PartyJoinable,
+}
+
+/// An event of a [`PresenceWatcher`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresenceEvent {
+    pub player: String,
+    pub platform: Platform,
+    pub change: PresenceChange,
+}
+
+/// A failed lookup of a player watched by a [`PresenceWatcher`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresenceError {
+    pub player: String,
+    pub platform: Platform,
+    pub error: Error,
+}
+
+impl fmt::Display for PresenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {}: {}", self.player, self.platform, self.error)
+    }
+}
+
+impl std::error::Error for PresenceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The parts of [`ApexRealtime`] that are watched
+#[derive(Debug, Clone)]
+struct Presence {
+    online: bool,
+    in_match: bool,
+    legend: String,
+    joinable: bool,
+}
+
+impl Presence {
+    fn new(realtime: &ApexRealtime) -> Self {
+        let online = realtime.is_online != 0;
+
+        Self {
+            online,
+            in_match: online && realtime.is_in_game != 0,
+            legend: realtime.selected_legend.clone(),
+            joinable: online && realtime.can_join != 0 && realtime.party_full == 0,
+        }
+    }
+
+    /// The changes from `self` to `now`, in the order they most likely happened
+    fn changes(&self, now: &Presence) -> Vec<PresenceChange> {
+        let mut changes = Vec::new();
+
+        if now.online && !self.online {
+            changes.push(PresenceChange::CameOnline);
+        }
+        if now.in_match && !self.in_match {
+            changes.push(PresenceChange::EnteredMatch);
+        }
+        if !now.in_match && self.in_match {
+            changes.push(PresenceChange::LeftMatch);
+        }
+        if now.online && self.online && now.legend != self.legend {
+            changes.push(PresenceChange::ChangedLegend {
+                previous: self.legend.clone(),
+                legend: now.legend.clone(),
+            });
+        }
+        if now.joinable && !self.joinable {
+            changes.push(PresenceChange::PartyJoinable);
+        }
+        if !now.online && self.online {
+            changes.push(PresenceChange::WentOffline);
+        }
+
+        changes
+    }
+}
+
+struct WatchedPlayer {
+    name: String,
+    platform: Platform,
+    presence: Option<Presence>,
+    /// When to look the player up again
+    due: Instant,
+}
+
+/// Polls the presence of some players and yields an event whenever it changes:
+/// they come online, go offline, enter or leave a match, change legend, or their party becomes joinable.
+///
+/// Each player is looked up at most once per `interval`, and the requests are spaced out so they never
+/// go over `requests_per_second`, leaving the rest of the rate limit of the API key to other requests.
+/// The lookups skip the response cache of the client, so a long player TTL never hides a change.
+///
+/// # Examples
+/// ```no_run
+/// use apex_legends_api::{data_types::Platform, watch::{PresenceChange, PresenceWatcher}, ApexClient};
+/// use futures::StreamExt;
+///
+/// async fn notify() {
+///     let client = ApexClient::new("your_api_key");
+///     let watcher = PresenceWatcher::new(client)
+///         .add_player("HeyImLifeline", Platform::Pc)
+///         .add_player("HeyImBangalore", Platform::Playstation);
+///     let mut events = Box::pin(watcher.stream());
+///
+///     while let Some(event) = events.next().await {
+///         match event {
+///             Ok(event) if event.change == PresenceChange::EnteredMatch => {
+///                 println!("{} is now playing", event.player)
+///             }
+///             Ok(_) => {}
+///             // The player is looked up again at the next interval
+///             Err(e) => println!("Could not look up {}: {}", e.player, e.error),
+///         }
+///     }
+/// }
+/// ```
+pub struct PresenceWatcher<T = ReqwestTransport> {
+    client: ApexClient<T>,
+    players: Vec<(String, Platform)>,
+    interval: Duration,
+    requests_per_second: f32,
+}
+
+impl<T: Transport> PresenceWatcher<T> {
+    pub fn new(client: ApexClient<T>) -> Self {
+        Self {
+            client,
+            players: Vec::new(),
+            interval: Duration::from_secs(30),
+            requests_per_second: 1.0,
+        }
+    }
+
+    /// Watches a player
+    ///
+    /// # Parameters
+    /// * `name` - The name of the player on the platform, see [`crate::player_name::PlayerName`]
+    /// * `platform` - The platform of the player
+    pub fn add_player(mut self, name: &str, platform: Platform) -> Self {
+        self.players.push((name.to_string(), platform));
+        self
+    }
+
+    /// The time between two lookups of the same player, 30 seconds by default.
+    /// With many players, it takes longer to go around all of them within `requests_per_second`.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The share of the rate limit of the API key that the watcher can use, 1 request per second by default
+    pub fn requests_per_second(mut self, requests_per_second: f32) -> Self {
+        self.requests_per_second = requests_per_second;
+        self
+    }
+
+    /// The events, for every player.
+    /// The first lookup of a player only records its presence, the events start with the next one.
+    /// Errors (e.g. a player that does not exist) are yielded too, with the player they are about,
+    /// and the stream goes on. It only ends if there is no player to watch.
+    pub fn stream(self) -> impl Stream<Item = Result<PresenceEvent, PresenceError>> {
+        let now = Instant::now();
+        let spacing = Duration::from_secs_f32(1.0 / self.requests_per_second.max(0.001));
+        let players = self
+            .players
+            .iter()
+            .map(|(name, platform)| WatchedPlayer {
+                name: name.clone(),
+                platform: *platform,
+                presence: None,
+                due: now,
+            })
+            .collect();
+        let state = PresenceState {
+            watcher: self,
+            players,
+            events: VecDeque::new(),
+            spacing,
+            last_request: None,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.events.pop_front() {
+                    return Some((Ok(event), state));
+                }
+
+                let next = state.players.iter().enumerate().min_by_key(|(_, p)| p.due);
+                let (index, due) = match next {
+                    Some((index, player)) => (index, player.due),
+                    None => return None,
+                };
+                let at = match state.last_request {
+                    Some(last) => due.max(last + state.spacing),
+                    None => due,
+                };
+                let now = Instant::now();
+                if at > now {
+                    rt::sleep(at - now).await;
+                }
+
+                state.last_request = Some(Instant::now());
+                let player = &mut state.players[index];
+                player.due = Instant::now() + state.watcher.interval;
+                let user = state
+                    .watcher
+                    .client
+                    .get_fresh_user_on_platform(&player.name, player.platform)
+                    .await;
+
+                match user {
+                    Ok(user) => {
+                        let presence = Presence::new(&user.realtime);
+                        if let Some(previous) = player.presence.replace(presence.clone()) {
+                            for change in previous.changes(&presence) {
+                                state.events.push_back(PresenceEvent {
+                                    player: player.name.clone(),
+                                    platform: player.platform,
+                                    change,
+                                });
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        let error = PresenceError {
+                            player: player.name.clone(),
+                            platform: player.platform,
+                            error,
+                        };
+                        return Some((Err(error), state));
+                    }
+                }
+            }
+        })
+    }
+}
+
+struct PresenceState<T> {
+    watcher: PresenceWatcher<T>,
+    players: Vec<WatchedPlayer>,
+    events: VecDeque<PresenceEvent>,
+    /// The minimum time between two requests
+    spacing: Duration,
+    last_request: Option<Instant>,
+}
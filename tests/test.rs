@@ -1,119 +1,1905 @@
+// Without a runtime nor the `blocking` feature only the data type tests are left, the mock server is unused
+#![cfg_attr(
+    not(any(
+        feature = "runtime-tokio",
+        feature = "runtime-async-std",
+        feature = "blocking"
+    )),
+    allow(dead_code, unused_imports)
+)]
+
 #[cfg(test)]
 mod tests {
-    use apex_legends_api::data_types;
-    use std::env;
+    use apex_legends_api::{
+        client::Endpoint,
+        data_types::{self, Platform},
+        mock::{MockGame, MockPlayer, MockRank, MockServer},
+        transport::StatusCode,
+        Error,
+    };
+    use std::{env, time::Duration};
 
-    fn print_data<T>(res: Result<T, String>, f: fn(T) -> String) -> bool {
-        match res {
-            Ok(data) => {
-                println!("{}", f(data));
+    // The async client and the watchers need one of the runtimes, the other tests also run with only `blocking`
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    use apex_legends_api::ApexClient;
 
-                true
-            }
-            Err(e) => {
-                println!("there was an error!: {}", e);
+    /// A response of the API, recorded in `tests/fixtures`
+    fn fixture(name: &str) -> String {
+        let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
 
-                false
-            }
+        std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("Missing fixture {}", path))
+    }
+
+    fn parse<T: serde::de::DeserializeOwned>(name: &str) -> T {
+        serde_json::from_str(&fixture(name))
+            .unwrap_or_else(|e| panic!("Could not deserialize {}: {}", name, e))
+    }
+
+    /// A mock server with the players of the fixtures, the games of `HeyImLifeline`
+    /// and the recorded server status, where Asia is down
+    fn server() -> MockServer {
+        let server = MockServer::start();
+        let lifeline = MockPlayer::new("HeyImLifeline", Platform::Pc)
+            .uid(1000575311440)
+            .level(512)
+            .to_next_level_percent(37)
+            .rank(MockRank::new(10842, "Diamond", 2))
+            .arena(MockRank::new(4321, "Platinum", 1));
+
+        server.set_games(
+            lifeline.uid,
+            vec![
+                MockGame::new("Lifeline", 1637597100).score(10842, 87),
+                MockGame::new("Lifeline", 1637595000).score(10755, -24),
+                MockGame::new("Bloodhound", 1637593800).arenas(),
+            ],
+        );
+        server.add_player(lifeline);
+        server.add_player(MockPlayer::new("HeyImBangalore", Platform::Playstation));
+        server.add_player(MockPlayer::new("HeyImWraith", Platform::Xbox));
+        server.add_player(MockPlayer::new("HeyImPathfinder", Platform::Switch));
+        server.add_player(
+            MockPlayer::new("HeyImBanned", Platform::Pc).banned("COMPETITIVE_DODGE_COOLDOWN", 1800),
+        );
+        server.set_response(Endpoint::ServerStatus, 200, &fixture("servers.json"));
+
+        server
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    fn client(server: &MockServer) -> ApexClient {
+        ApexClient::builder("test-key")
+            .base_url(server.url())
+            .build()
+    }
+
+    #[test]
+    fn deserialize_players() {
+        for (file, name, platform) in [
+            ("player_pc.json", "HeyImLifeline", "PC"),
+            ("player_ps4.json", "HeyImBangalore", "PS4"),
+            ("player_x1.json", "HeyImWraith", "X1"),
+            ("player_switch.json", "HeyImPathfinder", "SWITCH"),
+            ("player_banned.json", "HeyImBanned", "PC"),
+        ] {
+            let user: data_types::ApexUser = parse(file);
+
+            assert_eq!(user.global.name, name);
+            assert_eq!(user.global.platform, platform);
+            assert_eq!(user.global.battlepass.history.season10, 110);
+            assert!(user.stats.br_kills.value > 0);
+            assert_eq!(user.stats.kd.value, "-1");
+            // Not sent by the API, filled in by default
+            assert_eq!(user.stats.arenas_damage.value, 0);
         }
+
+        let user: data_types::ApexUser = parse("player_pc.json");
+        assert_eq!(user.global.uid, 1000575311440);
+        assert_eq!(user.global.level, 512);
+        assert_eq!(user.global.to_next_level_percent, 37);
+        assert_eq!(user.global.rank.rank_score, 10842);
+        assert_eq!(user.global.rank.rank_name, "Diamond");
+        assert_eq!(user.global.rank.rank_division, 2);
+        assert_eq!(user.global.arena.rank_name, "Platinum");
+        assert_eq!(user.realtime.is_online, 1);
+        assert_eq!(user.realtime.selected_legend, "Lifeline");
+        assert!(!user.global.bans.is_active);
+
+        let user: data_types::ApexUser = parse("player_banned.json");
+        assert!(user.global.bans.is_active);
+        assert_eq!(user.global.bans.remaining_seconds, 1800);
+        assert_eq!(
+            user.global.bans.last_ban_reason,
+            "COMPETITIVE_DODGE_COOLDOWN"
+        );
+    }
+
+    #[test]
+    fn deserialize_errors() {
+        let error: data_types::ApexError = parse("player_unknown.json");
+        assert_eq!(error.message, "Player HeyImNobody not found");
+
+        let error: data_types::ApexError = parse("rate_limit.json");
+        assert!(error.message.contains("rate limit"));
+    }
+
+    #[test]
+    fn deserialize_map_rotation() {
+        let rotation: data_types::ApexMapRotation = parse("map_rotation.json");
+
+        assert_eq!(rotation.battle_royale.current.map, "Olympus");
+        assert_eq!(rotation.battle_royale.next.map, "Storm Point");
+        assert_eq!(rotation.battle_royale.current.duration_in_minutes, 90);
+        assert_eq!(
+            rotation.battle_royale.current.readable_date_start,
+            "2021-11-22 16:00:00"
+        );
+        assert_eq!(rotation.arenas.current.map, "Phase Runner");
+        assert_eq!(rotation.ranked.current.map, "Storm Point");
+        assert_eq!(rotation.arenas_ranked.next.map, "Drop Off");
+        assert_eq!(rotation.next_change(), 1637598300);
+    }
+
+    #[test]
+    fn deserialize_match_history() {
+        let games: Vec<data_types::ApexGame> = parse("match_history.json");
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[0].legend_played, "Lifeline");
+        assert_eq!(games[0].game_end_timestamp, 1637597100);
+        assert_eq!(games[0].br_score_change, 87);
+        assert_eq!(games[0].game_data[0].name.as_deref(), Some("BR Kills"));
+        assert_eq!(games[0].cosmetics.skin_rarity, "Epic");
+        assert_eq!(games[1].game_data[1].name, None);
+        assert_eq!(games[2].game_mode, "ARENAS");
+        assert_eq!(games[2].game_data[0].name, None);
+    }
+
+    #[test]
+    fn deserialize_other_endpoints() {
+        let profile: data_types::ApexProfile = parse("nametouid.json");
+        assert_eq!(profile.uid, "1000575311440");
+
+        let status: data_types::ServerStatus = parse("servers.json");
+        assert_eq!(
+            status.down_services(&data_types::Region::Asia),
+            vec!["Apex Legends servers", "Crossplay auth"]
+        );
+        assert_eq!(
+            status.ea_novafusion[&data_types::Region::SouthAmerica].status,
+            data_types::ServiceHealth::Slow
+        );
+        assert!(!status.is_anything_down(&data_types::Region::EuWest));
+
+        // The regions that are not known yet are kept apart
+        let mut doc = json_fixture("servers.json");
+        doc["Origin_login"]["Oceania"] = doc["Origin_login"]["Asia"].clone();
+        doc["Origin_login"]["Africa"] = doc["Origin_login"]["Asia"].clone();
+        let status: data_types::ServerStatus = serde_json::from_value(doc).unwrap();
+        let oceania = data_types::Region::Unknown("Oceania".to_string());
+        assert_eq!(status.origin_login.len(), 9);
+        assert!(status.origin_login.contains_key(&oceania));
+        assert_eq!(serde_json::to_value(&oceania).unwrap(), "Oceania");
+        // Looked up by reference, without cloning the region
+        assert!(!status.is_anything_down(&oceania));
+
+        let thresholds: data_types::PredatorThresholds = parse("predator.json");
+        assert_eq!(thresholds.battle_royale.pc.value, 15108);
+        assert_eq!(
+            thresholds.arenas.get(data_types::Platform::Switch).value,
+            6700
+        );
+        let platform: data_types::Platform = "PS4".parse().unwrap();
+        assert_eq!(thresholds.battle_royale.get(platform).value, 14210);
+        assert_eq!(thresholds.battle_royale.pc.points_to_predator(10842), 4266);
     }
 
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
     #[tokio::test]
     async fn user() {
-        dotenv::dotenv().expect("Could not load .env file");
+        let server = server();
+        let client = client(&server);
 
-        let user_name = env::var("USERNAME").expect("Expected key USERNAME");
-        let api_key = env::var("API_KEY").expect("Expected key API_KEY");
+        for (name, platform) in [
+            ("HeyImLifeline", Platform::Pc),
+            ("HeyImBangalore", Platform::Playstation),
+            ("HeyImWraith", Platform::Xbox),
+            ("HeyImPathfinder", Platform::Switch),
+        ] {
+            let user = client.get_user_on_platform(name, platform).await.unwrap();
 
-        assert!(
-            print_data::<data_types::ApexUser>(
-                apex_legends_api::get_user_retry(String::from(&user_name), &api_key, true).await,
-                |data| {
-                    format!(
-                        "You are level {}, and you have {} kills.",
-                        data.global.level, data.stats.br_kills.value
-                    )
-                },
-            ),
-            "get_user_retry"
+            assert_eq!(user.global.name, name);
+            assert_eq!(user.global.platform, platform.as_str());
+        }
+
+        let user = client.get_user("HeyImLifeline").await.unwrap();
+        assert_eq!(user.global.rank.rank_score, 10842);
+
+        let user = client.get_user("HeyImBanned").await.unwrap();
+        assert!(user.global.bans.is_active);
+        assert_eq!(user.global.bans.remaining_seconds, 1800);
+
+        assert_eq!(
+            client.get_user("HeyImNobody").await.err(),
+            Some(Error::Api("Player HeyImNobody not found".to_string()))
         );
+    }
 
-        assert!(
-            print_data::<data_types::ApexUser>(
-                apex_legends_api::get_user_retry(String::from(&user_name), &api_key, true).await,
-                |data| {
-                    format!(
-                        "You are level {}, and you have {} kills.",
-                        data.global.level, data.stats.br_kills.value
-                    )
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn uid() {
+        let server = server();
+        let client = client(&server);
+
+        let profile = client
+            .get_uid_from_username("HeyImLifeline", data_types::Platform::Pc)
+            .await
+            .unwrap();
+        assert_eq!(profile.uid, "1000575311440");
+
+        let found = client
+            .find_uids_from_username("HeyImLifeline")
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].platform, data_types::Platform::Pc);
+
+        assert!(client
+            .find_uids_from_username("HeyImNobody")
+            .await
+            .unwrap()
+            .is_empty());
+
+        // The other errors of the API are not taken for a missing player
+        server.set_response(Endpoint::NameToUid, 200, r#"{"Error": "Invalid API key"}"#);
+        assert_eq!(
+            client.find_uids_from_username("HeyImLifeline").await.err(),
+            Some(Error::Api("Invalid API key".to_string()))
+        );
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn games() {
+        use apex_legends_api::games::{GameMode, GamesQuery};
+        use futures::TryStreamExt;
+
+        let server = server();
+        let client = client(&server);
+
+        let games = client.get_recent_games("1000575311440").await.unwrap();
+        assert_eq!(games.len(), 3);
+
+        // Two pages of 2 games, the second one stops before the oldest game
+        let games: Vec<_> = client
+            .games_history(GamesQuery::new("1000575311440").limit(2), 1637594000)
+            .try_collect()
+            .await
+            .unwrap();
+        let ends: Vec<_> = games.iter().map(|g| g.game_end_timestamp).collect();
+        assert_eq!(ends, vec![1637597100, 1637595000]);
+        assert_eq!(server.hits(), 3);
+
+        client
+            .get_games(
+                &GamesQuery::new("1000575311440")
+                    .mode(GameMode::BattleRoyale)
+                    .limit(5)
+                    .add_to_tracking(true),
+            )
+            .await
+            .unwrap();
+        let params = server.requests().pop().unwrap().params;
+        assert_eq!(params["uid"], "1000575311440");
+        assert_eq!(params["mode"], "BATTLE_ROYALE");
+        assert_eq!(params["limit"], "5");
+        assert_eq!(params["action"], "add");
+        assert!(!params.contains_key("add"));
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn games_sharing_a_timestamp() {
+        use apex_legends_api::games::GamesQuery;
+        use futures::TryStreamExt;
+
+        let server = server();
+        let player = MockPlayer::new("HeyImLifeline", Platform::Pc);
+        // Two games end at the boundary of the first two pages
+        server.set_games(
+            player.uid,
+            vec![
+                MockGame::new("Lifeline", 1637597100),
+                MockGame::new("Lifeline", 1637595000),
+                MockGame::new("Bloodhound", 1637595000),
+                MockGame::new("Octane", 1637593800),
+            ],
+        );
+        let uid = player.uid.to_string();
+        server.add_player(player);
+
+        let games: Vec<_> = client(&server)
+            .games_history(GamesQuery::new(uid).limit(2), 0)
+            .try_collect()
+            .await
+            .unwrap();
+        let games: Vec<_> = games
+            .iter()
+            .map(|g| (g.game_end_timestamp, g.legend_played.as_str()))
+            .collect();
+        assert_eq!(
+            games,
+            vec![
+                (1637597100, "Lifeline"),
+                (1637595000, "Lifeline"),
+                (1637595000, "Bloodhound"),
+                (1637593800, "Octane"),
+            ]
+        );
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn games_sharing_the_until_timestamp() {
+        use apex_legends_api::games::GamesQuery;
+        use futures::TryStreamExt;
+
+        let server = server();
+        let player = MockPlayer::new("HeyImLifeline", Platform::Pc);
+        // The last game of the first page and the games after it all end at `until`
+        server.set_games(
+            player.uid,
+            vec![
+                MockGame::new("Lifeline", 1637597100),
+                MockGame::new("Bloodhound", 1637595000),
+                MockGame::new("Octane", 1637593800),
+                MockGame::new("Wraith", 1637593800),
+                MockGame::new("Gibraltar", 1637593800),
+                MockGame::new("Bangalore", 1637590000),
+            ],
+        );
+        let uid = player.uid.to_string();
+        server.add_player(player);
+
+        let games: Vec<_> = client(&server)
+            .games_history(GamesQuery::new(uid).limit(3), 1637593800)
+            .try_collect()
+            .await
+            .unwrap();
+        let mut legends: Vec<_> = games.iter().map(|g| g.legend_played.as_str()).collect();
+        legends.sort_unstable();
+        assert_eq!(
+            legends,
+            vec!["Bloodhound", "Gibraltar", "Lifeline", "Octane", "Wraith"]
+        );
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn map_rotation_servers_and_predator() {
+        let server = server();
+        let client = client(&server);
+
+        let rotation = client.get_map_rotation().await.unwrap();
+        assert_eq!(rotation.battle_royale.current.map, "Olympus");
+
+        let status = client.get_server_status().await.unwrap();
+        assert!(status.is_anything_down(&data_types::Region::Asia));
+
+        let thresholds = client.get_predator_thresholds().await.unwrap();
+        assert!(thresholds.arenas.pc.value > thresholds.arenas.switch.value);
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn rate_limit() {
+        use apex_legends_api::mock::{RateLimit, RateLimitStyle};
+
+        let server = server();
+
+        // The API usually reports the rate limit with a 200 OK
+        for style in [RateLimitStyle::OkWithError, RateLimitStyle::TooManyRequests] {
+            server.set_rate_limit(Some(RateLimit {
+                per_second: 1,
+                style,
+            }));
+
+            let client = ApexClient::builder("limited")
+                .base_url(server.url())
+                .retry(false)
+                .build();
+            assert!(client.get_map_rotation().await.is_ok());
+            assert_eq!(
+                client.get_map_rotation().await.err(),
+                Some(Error::Status(StatusCode::TOO_MANY_REQUESTS))
+            );
+
+            // The request is sent again with the next key, even without retry
+            let client = ApexClient::builder("limited")
+                .add_api_key("test-key")
+                .base_url(server.url())
+                .retry(false)
+                .build();
+            assert!(client.get_map_rotation().await.is_ok());
+            let requests = server.requests();
+            let keys: Vec<_> = requests[requests.len() - 2..]
+                .iter()
+                .map(|r| r.headers["authorization"].as_str())
+                .collect();
+            assert_eq!(keys, vec!["limited", "test-key"]);
+        }
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn revoked_key() {
+        let server = server();
+        server.set_api_keys(&["test-key"]);
+
+        let client = ApexClient::builder("revoked")
+            .base_url(server.url())
+            .build();
+        assert_eq!(
+            client.get_server_status().await.err(),
+            Some(Error::Status(StatusCode::UNAUTHORIZED))
+        );
+
+        let client = ApexClient::builder("revoked")
+            .add_api_key("test-key")
+            .base_url(server.url())
+            .build();
+        assert!(client.get_server_status().await.is_ok());
+        assert!(client.get_server_status().await.is_ok());
+        // The revoked key is not used anymore
+        assert_eq!(server.hits(), 4);
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn keys_in_turns() {
+        let server = server();
+        let client = ApexClient::builder("first")
+            .add_api_key("second")
+            .add_api_key("third")
+            .base_url(server.url())
+            .build();
+
+        for _ in 0..4 {
+            assert!(client.get_server_status().await.is_ok());
+        }
+        let keys: Vec<_> = server
+            .requests()
+            .iter()
+            .map(|r| r.headers["authorization"].clone())
+            .collect();
+        assert_eq!(keys, vec!["first", "second", "third", "first"]);
+    }
+
+    /// Answers 429 once to each key of `limited`, with this `x-current-rate` header, and 200 OK otherwise
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    struct RateLimited {
+        rate: &'static str,
+        limited: std::sync::Mutex<Vec<&'static str>>,
+        keys: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    impl RateLimited {
+        fn new(rate: &'static str, limited: &[&'static str]) -> Self {
+            Self {
+                rate,
+                limited: std::sync::Mutex::new(limited.to_vec()),
+                keys: Default::default(),
+            }
+        }
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    impl apex_legends_api::transport::Transport for RateLimited {
+        fn send(
+            &self,
+            request: apex_legends_api::transport::Request,
+        ) -> futures::future::BoxFuture<
+            '_,
+            Result<apex_legends_api::transport::Response, apex_legends_api::Error>,
+        > {
+            use apex_legends_api::transport::{HeaderMap, Response};
+
+            let key = request.headers["authorization"]
+                .to_str()
+                .unwrap()
+                .to_string();
+            self.keys.lock().unwrap().push(key.clone());
+
+            let mut limited = self.limited.lock().unwrap();
+            let response = match limited.iter().position(|k| *k == key) {
+                Some(position) => {
+                    limited.remove(position);
+                    let mut headers = HeaderMap::new();
+                    headers.insert("x-current-rate", self.rate.parse().unwrap());
+
+                    Response {
+                        status: StatusCode::TOO_MANY_REQUESTS,
+                        headers,
+                        body: String::new(),
+                    }
+                }
+                None => Response {
+                    status: StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: fixture("servers.json"),
                 },
-            ),
-            "get_user_retry"
+            };
+
+            Box::pin(futures::future::ready(Ok(response)))
+        }
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn benched_key_comes_back() {
+        let transport = std::sync::Arc::new(RateLimited::new("0.2", &["first"]));
+        let client = ApexClient::builder("first")
+            .add_api_key("second")
+            .retry(false)
+            .build_with_transport(transport.clone());
+
+        assert!(client.get_server_status().await.is_ok());
+        // The first key is still benched
+        assert!(client.get_server_status().await.is_ok());
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(client.get_server_status().await.is_ok());
+
+        assert_eq!(
+            *transport.keys.lock().unwrap(),
+            vec!["first", "second", "second", "first"]
+        );
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn invalid_current_rate() {
+        use apex_legends_api::mock::{RateLimit, RateLimitStyle};
+
+        let server = server();
+        server.set_rate_limit(Some(RateLimit {
+            per_second: 0,
+            style: RateLimitStyle::TooManyRequests,
+        }));
+
+        // Falls back to DEFAULT_RATE instead of panicking
+        for rate in ["-1", "NaN", "1e40"] {
+            server.set_current_rate(rate);
+
+            let client = ApexClient::builder("limited")
+                .base_url(server.url())
+                .retry(false)
+                .build();
+            assert_eq!(
+                client.get_map_rotation().await.err(),
+                Some(Error::Status(StatusCode::TOO_MANY_REQUESTS))
+            );
+        }
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn cached_requests() {
+        let server = server();
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .cache(apex_legends_api::cache::CacheConfig::default())
+            .build();
+
+        for _ in 0..2 {
+            assert!(client.get_server_status().await.is_ok());
+        }
+        assert_eq!(server.hits(), 1);
+
+        // TTLs under a second are kept too
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .cache(
+                apex_legends_api::cache::CacheConfig::empty()
+                    .ttl(Endpoint::ServerStatus, Duration::from_millis(300)),
+            )
+            .build();
+        for _ in 0..2 {
+            assert!(client.get_server_status().await.is_ok());
+        }
+        assert_eq!(server.hits(), 2);
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(client.get_server_status().await.is_ok());
+        assert_eq!(server.hits(), 3);
+
+        // A rotation without any end is cached for the whole TTL
+        server.set_response(
+            Endpoint::MapRotation,
+            200,
+            r#"{"battle_royale": {"current": {"map": "Olympus"}, "next": {"map": "Storm Point"}}}"#,
         );
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .cache(apex_legends_api::cache::CacheConfig::default())
+            .build();
+        for _ in 0..2 {
+            assert!(client.get_map_rotation().await.is_ok());
+        }
+        assert_eq!(server.hits(), 4);
     }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
     #[tokio::test]
-    async fn uid() {
-        dotenv::dotenv().expect("Could not load .env file");
+    async fn coalesced_requests() {
+        let server = server();
+        let client = client(&server);
 
-        let user_name = env::var("USERNAME").expect("Expected key USERNAME");
-        let api_key = env::var("API_KEY").expect("Expected key API_KEY");
+        let (first, second) =
+            futures::join!(client.get_server_status(), client.get_server_status());
 
-        assert!(
-            print_data::<data_types::ApexProfile>(
-                apex_legends_api::get_uid_from_username_retry(
-                    String::from(&user_name),
-                    &api_key,
-                    true
-                )
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn record_and_replay() {
+        let server = server();
+        let cassette = env::temp_dir().join(format!("apex_cassette_{}.json", std::process::id()));
+
+        let client = ApexClient::builder("secret-key")
+            .base_url(server.url())
+            .build_recording(&cassette)
+            .unwrap();
+        client.get_user("HeyImLifeline").await.unwrap();
+        assert!(client.get_user("HeyImNobody").await.is_err());
+        // Only written when asked to, or when the client is dropped
+        assert!(!cassette.exists());
+        client.save_cassette().unwrap();
+
+        let recorded = std::fs::read_to_string(&cassette).unwrap();
+        assert!(recorded.contains("/bridge?version=5&platform=PC&player=HeyImLifeline"));
+        assert!(!recorded.contains("secret-key"));
+        assert!(!recorded.contains(server.url()));
+
+        // Nothing listens on the base URL anymore, every answer comes from the cassette
+        let client = ApexClient::builder("other-key")
+            .base_url("http://127.0.0.1:9")
+            .build_replaying(&cassette)
+            .unwrap();
+        assert_eq!(
+            client.get_user("HeyImLifeline").await.unwrap().global.level,
+            512
+        );
+        assert_eq!(
+            client.get_user("HeyImNobody").await.err(),
+            Some(Error::Api("Player HeyImNobody not found".to_string()))
+        );
+        assert!(matches!(
+            client.get_map_rotation().await,
+            Err(Error::Cassette(_))
+        ));
+
+        std::fs::remove_file(&cassette).unwrap();
+
+        let client = ApexClient::builder("secret-key")
+            .base_url(server.url())
+            .build_recording(&cassette)
+            .unwrap();
+        client.get_map_rotation().await.unwrap();
+        drop(client);
+        assert!(std::fs::read_to_string(&cassette)
+            .unwrap()
+            .contains("/maprotation?version=2"));
+        std::fs::remove_file(&cassette).unwrap();
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn mock_server() {
+        use apex_legends_api::games::GamesQuery;
+
+        let server = MockServer::start();
+        let player = MockPlayer::new("HeyImLifeline", data_types::Platform::Pc)
+            .rank(MockRank::new(10842, "Diamond", 2))
+            .in_game(true);
+        let uid = player.uid;
+        server.add_player(player);
+        server.set_games(
+            uid,
+            vec![
+                MockGame::new("Lifeline", 1637597100),
+                MockGame::new("Bloodhound", 1637593800).arenas(),
+            ],
+        );
+
+        let client = ApexClient::builder("any-key")
+            .base_url(server.url())
+            .build();
+
+        let user = client.get_user("HeyImLifeline").await.unwrap();
+        assert_eq!(user.global.rank.rank_name, "Diamond");
+        assert_eq!(user.realtime.current_state, "inMatch");
+        assert_eq!(
+            client
+                .get_uid_from_username("HeyImLifeline", data_types::Platform::Pc)
+                .await
+                .unwrap()
+                .uid,
+            uid.to_string()
+        );
+        assert!(client.get_user("HeyImNobody").await.is_err());
+
+        let games = client
+            .get_games(&GamesQuery::new(uid.to_string()).limit(1))
+            .await
+            .unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].legend_played, "Lifeline");
+
+        let rotation = client.get_map_rotation().await.unwrap();
+        assert_eq!(rotation.battle_royale.current.map, "Olympus");
+        assert!(rotation.next_change() > 0);
+        assert!(client.get_server_status().await.is_ok());
+        assert!(client.get_predator_thresholds().await.is_ok());
+
+        server.set_response(Endpoint::ServerStatus, 500, "");
+        assert_eq!(
+            client.get_server_status().await.err(),
+            Some(Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
+        );
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn mock_server_limits() {
+        use apex_legends_api::mock::{RateLimit, RateLimitStyle};
+        use std::time::Duration;
+
+        let server = MockServer::start();
+
+        for style in [RateLimitStyle::TooManyRequests, RateLimitStyle::OkWithError] {
+            // A new client, the previous one does not use its rate limited key for a while
+            let client = ApexClient::builder("any-key")
+                .base_url(server.url())
+                .retry(false)
+                .build();
+            server.set_rate_limit(Some(RateLimit {
+                per_second: 1,
+                style,
+            }));
+
+            assert!(client.get_map_rotation().await.is_ok());
+            assert_eq!(
+                client.get_map_rotation().await.err(),
+                Some(Error::Status(StatusCode::TOO_MANY_REQUESTS))
+            );
+        }
+        server.set_rate_limit(None);
+
+        server.set_api_keys(&["good-key"]);
+        let client = ApexClient::builder("any-key")
+            .base_url(server.url())
+            .build();
+        assert_eq!(
+            client.get_map_rotation().await.err(),
+            Some(Error::Status(StatusCode::UNAUTHORIZED))
+        );
+
+        server.set_latency(Duration::from_millis(500));
+        let client = ApexClient::builder("good-key")
+            .base_url(server.url())
+            .read_timeout(Duration::from_millis(100))
+            .build();
+        assert_eq!(client.get_map_rotation().await.err(), Some(Error::Timeout));
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn builder_options() {
+        use apex_legends_api::client::{
+            header::{HeaderMap, HeaderValue},
+            Proxy,
+        };
+
+        let server = server();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-gateway", HeaderValue::from_static("apex"));
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .user_agent("apex-tests")
+            .default_headers(headers)
+            .build();
+        client.get_map_rotation().await.unwrap();
+
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.headers["x-gateway"], "apex");
+        assert_eq!(request.headers["user-agent"], "apex-tests");
+        assert_eq!(request.headers["authorization"], "test-key");
+
+        // The API is never reached, the proxy answers in its place
+        let client = ApexClient::builder("test-key")
+            .base_url("http://api.apex.invalid")
+            .proxy(Proxy::http(server.url()).unwrap())
+            .build();
+        client.get_map_rotation().await.unwrap();
+        assert_eq!(
+            server.requests().pop().unwrap().headers["host"],
+            "api.apex.invalid"
+        );
+
+        server.set_latency(Duration::from_millis(500));
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .timeout(Duration::from_millis(100))
+            .build();
+        let error = client.get_map_rotation().await.unwrap_err();
+        assert_eq!(error, Error::Timeout);
+        assert!(error.is_retryable());
+
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .timeout(Duration::from_secs(5))
+            .build();
+        assert!(client.get_map_rotation().await.is_ok());
+    }
+
+    /// With only `runtime-async-std`, the client runs its requests in a background tokio runtime
+    #[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+    #[test]
+    fn without_tokio_runtime() {
+        use apex_legends_api::watch::{MapRotationEvent, MapRotationWatcher};
+        use futures::StreamExt;
+
+        let server = server();
+        server.set_response(Endpoint::MapRotation, 500, "");
+
+        futures::executor::block_on(async {
+            assert!(tokio::runtime::Handle::try_current().is_err());
+
+            let client = client(&server);
+            assert_eq!(
+                client.get_user("HeyImLifeline").await.unwrap().global.level,
+                512
+            );
+
+            // The retry delay uses the timers of async-io
+            let mut events = Box::pin(
+                MapRotationWatcher::new(client)
+                    .retry_delay(Duration::from_millis(100))
+                    .stream(),
+            );
+            assert!(events.next().await.unwrap().is_err());
+            server.clear_response(Endpoint::MapRotation);
+            assert!(matches!(
+                events.next().await.unwrap(),
+                Ok(MapRotationEvent::Started(_))
+            ));
+        });
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn body_errors() {
+        let server = server();
+        let limited = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .max_response_size(100)
+            .build();
+
+        let error = limited.get_map_rotation().await.unwrap_err();
+        assert_eq!(error, Error::ResponseTooLarge(100));
+        assert!(!error.is_retryable());
+
+        server.set_response_bytes(Endpoint::MapRotation, 200, b"{\"map\": \"\xff\"}");
+        let error = limited.get_map_rotation().await.unwrap_err();
+        assert_eq!(error, Error::InvalidUtf8);
+        assert!(!error.is_retryable());
+
+        server.clear_response(Endpoint::MapRotation);
+        server.set_disconnect_after(Some(10));
+        let client = client(&server);
+        let error = client.get_map_rotation().await.unwrap_err();
+        assert!(matches!(error, Error::Body(_)), "{:?}", error);
+        assert!(error.is_retryable());
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_client() {
+        let server = server();
+        let client = apex_legends_api::blocking::ApexClient::builder("test-key")
+            .base_url(server.url())
+            .build();
+
+        assert_eq!(client.get_user("HeyImLifeline").unwrap().global.level, 512);
+        assert_eq!(
+            client.get_map_rotation().unwrap().arenas.next.map,
+            "Party Crasher"
+        );
+        assert_eq!(
+            client
+                .games_history(apex_legends_api::games::GamesQuery::new("1000575311440"), 0)
+                .count(),
+            3
+        );
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_body_errors() {
+        let server = server();
+        let client = apex_legends_api::blocking::ApexClient::builder("test-key")
+            .base_url(server.url())
+            .max_response_size(100)
+            .build();
+
+        let error = client.get_map_rotation().unwrap_err();
+        assert_eq!(error, Error::ResponseTooLarge(100));
+        assert!(!error.is_retryable());
+
+        server.set_response_bytes(Endpoint::MapRotation, 200, b"{\"map\": \"\xff\"}");
+        let error = client.get_map_rotation().unwrap_err();
+        assert_eq!(error, Error::InvalidUtf8);
+        assert!(!error.is_retryable());
+
+        server.clear_response(Endpoint::MapRotation);
+        server.set_disconnect_after(Some(10));
+        let client = apex_legends_api::blocking::ApexClient::builder("test-key")
+            .base_url(server.url())
+            .build();
+        let error = client.get_map_rotation().unwrap_err();
+        assert!(matches!(error, Error::Body(_)), "{:?}", error);
+        assert!(error.is_retryable());
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_invalid_current_rate() {
+        use apex_legends_api::mock::{RateLimit, RateLimitStyle};
+
+        let server = server();
+        server.set_rate_limit(Some(RateLimit {
+            per_second: 0,
+            style: RateLimitStyle::TooManyRequests,
+        }));
+
+        for rate in ["-1", "NaN", "1e40"] {
+            server.set_current_rate(rate);
+
+            let client = apex_legends_api::blocking::ApexClient::builder("limited")
+                .base_url(server.url())
+                .retry(false)
+                .build();
+            assert_eq!(
+                client.get_map_rotation().err(),
+                Some(Error::Status(StatusCode::TOO_MANY_REQUESTS))
+            );
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_builder_options() {
+        use apex_legends_api::{
+            blocking::ApexClient,
+            client::header::{HeaderMap, HeaderValue},
+        };
+
+        let server = server();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-gateway", HeaderValue::from_static("apex"));
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .default_headers(headers)
+            .build();
+        client.get_map_rotation().unwrap();
+        assert_eq!(
+            server.requests().pop().unwrap().headers["x-gateway"],
+            "apex"
+        );
+
+        server.set_latency(Duration::from_millis(500));
+        for client in [
+            ApexClient::builder("test-key").timeout(Duration::from_millis(100)),
+            ApexClient::builder("test-key").read_timeout(Duration::from_millis(100)),
+        ] {
+            let client = client.base_url(server.url()).build();
+            assert_eq!(client.get_map_rotation().err(), Some(Error::Timeout));
+        }
+
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .timeout(Duration::from_secs(5))
+            .read_timeout(Duration::from_secs(5))
+            .build();
+        assert!(client.get_map_rotation().is_ok());
+    }
+
+    #[test]
+    fn file_cache_store() {
+        use apex_legends_api::cache::{CacheStore, CachedResponse, FileStore};
+
+        let dir = env::temp_dir().join(format!("apex_file_cache_{}", std::process::id()));
+        let store = FileStore::new(&dir);
+        let response = CachedResponse {
+            body: String::from("{}"),
+            expires_at_ms: u64::MAX,
+        };
+
+        store.put("maprotation?version=2", response);
+        // A new store on the same directory sees the responses of the previous one
+        let store = FileStore::new(&dir);
+
+        assert_eq!(
+            store.get("maprotation?version=2").map(|r| r.body),
+            Some(String::from("{}"))
+        );
+        assert!(store.get("servers?").is_none());
+
+        store.remove("maprotation?version=2");
+        assert!(store.get("maprotation?version=2").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[test]
+    fn client_debug_hides_api_key() {
+        let builder =
+            apex_legends_api::ApexClient::builder("secret-key").add_api_key("other-secret");
+        assert!(!format!("{:?}", builder).contains("secret"));
+
+        let client = builder.build();
+        assert!(!format!("{:?}", client).contains("secret"));
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn invalid_player_name() {
+        let client = apex_legends_api::ApexClient::new("no-key-needed");
+
+        assert!(matches!(
+            client.get_user("").await,
+            Err(apex_legends_api::Error::InvalidPlayerName(_))
+        ));
+        assert!(matches!(
+            client
+                .get_uid_from_username("a\nb", data_types::Platform::Pc)
                 .await,
-                |data| format!("Your UID is {}", data.uid),
-            ),
-            "get_uid_from_username_retry"
+            Err(apex_legends_api::Error::InvalidPlayerName(_))
+        ));
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn encoded_player_names() {
+        use std::collections::HashSet;
+
+        let server = server();
+        let client = client(&server);
+        let keys = |params: &std::collections::HashMap<String, String>| {
+            params.keys().cloned().collect::<HashSet<_>>()
+        };
+
+        for name in ["Rock & Roll#1", "Ünïcødé ?x=1"] {
+            server.add_player(MockPlayer::new(name, Platform::Pc));
+
+            assert_eq!(client.get_user(name).await.unwrap().global.name, name);
+            let params = server.requests().pop().unwrap().params;
+            assert_eq!(params["player"], name);
+            assert_eq!(
+                keys(&params),
+                ["version", "platform", "player"].map(String::from).into()
+            );
+
+            client
+                .get_uid_from_username(name, Platform::Pc)
+                .await
+                .unwrap();
+            let params = server.requests().pop().unwrap().params;
+            assert_eq!(params["player"], name);
+            assert_eq!(
+                keys(&params),
+                ["player", "platform"].map(String::from).into()
+            );
+
+            let found = client.find_uids_from_username(name).await.unwrap();
+            assert_eq!(found.len(), 1);
+            for request in &server.requests()[server.hits() - Platform::ALL.len()..] {
+                assert_eq!(request.params["player"], name);
+                assert_eq!(
+                    keys(&request.params),
+                    ["player", "platform"].map(String::from).into()
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_invalid_player_name() {
+        let client = apex_legends_api::blocking::ApexClient::new("no-key-needed");
+
+        assert!(matches!(
+            client.get_user(""),
+            Err(apex_legends_api::Error::InvalidPlayerName(_))
+        ));
+        assert!(!format!("{:?}", client).contains("no-key-needed"));
+    }
+
+    /// Answers every request with the same body and remembers the requests
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    struct Canned {
+        body: &'static str,
+        requests: std::sync::Mutex<Vec<apex_legends_api::transport::Request>>,
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    impl apex_legends_api::transport::Transport for Canned {
+        fn send(
+            &self,
+            request: apex_legends_api::transport::Request,
+        ) -> futures::future::BoxFuture<
+            '_,
+            Result<apex_legends_api::transport::Response, apex_legends_api::Error>,
+        > {
+            self.requests.lock().unwrap().push(request);
+
+            Box::pin(futures::future::ready(Ok(
+                apex_legends_api::transport::Response {
+                    status: apex_legends_api::transport::StatusCode::OK,
+                    headers: apex_legends_api::transport::HeaderMap::new(),
+                    body: self.body.to_string(),
+                },
+            )))
+        }
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn custom_transport() {
+        let transport = std::sync::Arc::new(Canned {
+            body: r#"{"name": "HeyImLifeline", "uid": "1000575311440", "pid": "1", "avatar": ""}"#,
+            requests: Default::default(),
+        });
+        let client = apex_legends_api::ApexClient::builder("secret-key")
+            .user_agent("tests")
+            .build_with_transport(transport.clone());
+
+        let profile = client
+            .get_uid_from_username("HeyImLifeline", data_types::Platform::Pc)
+            .await
+            .unwrap();
+        assert_eq!(profile.uid, "1000575311440");
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].url.contains("/nametouid?"));
+        assert!(!requests[0].url.contains("secret-key"));
+        assert_eq!(requests[0].headers["authorization"], "secret-key");
+        assert_eq!(requests[0].headers["user-agent"], "tests");
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn fake_data() {
+        let user: data_types::ApexUser = parse("player_pc.json");
+        let fake = data_types::ApexUser::fake();
+        assert_eq!(fake.global.name, user.global.name);
+        assert_eq!(fake.global.uid, user.global.uid);
+        assert_eq!(fake.global.rank.rank_score, user.global.rank.rank_score);
+        assert_eq!(fake.global.arena.rank_img, user.global.arena.rank_img);
+
+        let rotation = data_types::ApexMapRotation::fake();
+        for mode in [
+            &rotation.battle_royale,
+            &rotation.arenas,
+            &rotation.arenas_ranked,
+        ] {
+            assert_eq!(mode.current.end, mode.next.start);
+            assert_eq!(
+                mode.current.end - mode.current.start,
+                mode.current.duration_in_seconds as i64
+            );
+        }
+        assert_eq!(
+            rotation.battle_royale.current.readable_date_end,
+            "2021-11-22 17:30:00"
         );
+        // Across midnight, and on a leap day
+        let item = data_types::ApexMapRotationItem::fake_at(1709247600, 7200, "World's Edge");
+        assert_eq!(item.readable_date_start, "2024-02-29 23:00:00");
+        assert_eq!(item.readable_date_end, "2024-03-01 01:00:00");
+
+        let banned = data_types::ApexBans {
+            is_active: true,
+            ..data_types::ApexBans::fake()
+        };
+        assert_eq!(banned.last_ban_reason, "NONE");
+    }
+
+    /// JSON pointers to every value in `value`, except the root
+    fn pointers(value: &serde_json::Value, prefix: &str, out: &mut Vec<String>) {
+        let children: Vec<(String, &serde_json::Value)> = match value {
+            serde_json::Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| (k.replace('~', "~0").replace('/', "~1"), v))
+                .collect(),
+            serde_json::Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        for (key, child) in children {
+            let pointer = format!("{}/{}", prefix, key);
+            pointers(child, &pointer, out);
+            out.push(pointer);
+        }
+    }
+
+    /// Replaces the value at `pointer`, or removes it if `replacement` is `None`
+    fn mutate(doc: &mut serde_json::Value, pointer: &str, replacement: Option<serde_json::Value>) {
+        let (parent, key) = pointer.rsplit_once('/').unwrap();
+        let key = key.replace("~1", "/").replace("~0", "~");
+
+        match (doc.pointer_mut(parent).unwrap(), replacement) {
+            (serde_json::Value::Object(map), Some(value)) => {
+                map.insert(key, value);
+            }
+            (serde_json::Value::Object(map), None) => {
+                map.remove(&key);
+            }
+            (serde_json::Value::Array(items), Some(value)) => {
+                items[key.parse::<usize>().unwrap()] = value;
+            }
+            (serde_json::Value::Array(items), None) => {
+                items.remove(key.parse::<usize>().unwrap());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn any_json() -> impl proptest::strategy::Strategy<Value = serde_json::Value> {
+        use proptest::prelude::*;
+
+        let leaf = prop_oneof![
+            Just(serde_json::Value::Null),
+            any::<bool>().prop_map(serde_json::Value::from),
+            any::<i64>().prop_map(serde_json::Value::from),
+            any::<f64>().prop_map(serde_json::Value::from),
+            "[0-9]{1,6}".prop_map(serde_json::Value::from),
+            ".{0,8}".prop_map(serde_json::Value::from),
+        ];
+
+        leaf.prop_recursive(3, 16, 4, |inner| {
+            prop_oneof![
+                proptest::collection::vec(inner.clone(), 0..4).prop_map(serde_json::Value::from),
+                proptest::collection::btree_map(".{0,8}", inner, 0..4)
+                    .prop_map(|map| serde_json::Value::Object(map.into_iter().collect())),
+            ]
+        })
+    }
+
+    /// Replaces or removes one value of a fixture, then deserializes it.
+    ///
+    /// Only the mutation of a `required` field (or of one of its parents) may fail the whole document,
+    /// and the `known` fields that were not mutated must keep their value.
+    fn check_mutation<T, F>(
+        doc: &serde_json::Value,
+        mutation: (proptest::sample::Index, Option<serde_json::Value>),
+        required: &[&str],
+        known: F,
+    ) -> Result<(), proptest::test_runner::TestCaseError>
+    where
+        T: serde::de::DeserializeOwned,
+        F: Fn(&T) -> Vec<(&'static str, String)>,
+    {
+        let mut paths = Vec::new();
+        pointers(doc, "", &mut paths);
+        let pointer = mutation.0.get(&paths);
+        let affects = |field: &str| field == pointer || field.starts_with(&format!("{}/", pointer));
 
+        let mut mutated = doc.clone();
+        mutate(&mut mutated, pointer, mutation.1);
+        let parsed = serde_json::from_str::<T>(&mutated.to_string());
+
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                proptest::prop_assert!(
+                    required.iter().any(|field| affects(field)),
+                    "Mutating {} failed the document: {}",
+                    pointer,
+                    e
+                );
+                return Ok(());
+            }
+        };
+
+        let original: T = serde_json::from_value(doc.clone()).unwrap();
+        for ((field, value), (_, expected)) in known(&parsed).into_iter().zip(known(&original)) {
+            if !affects(field) {
+                proptest::prop_assert_eq!(
+                    value,
+                    expected,
+                    "{} changed after mutating {}",
+                    field,
+                    pointer
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn user_fields(user: &data_types::ApexUser) -> Vec<(&'static str, String)> {
+        vec![
+            ("/global/name", user.global.name.clone()),
+            ("/global/uid", user.global.uid.to_string()),
+            ("/global/level", user.global.level.to_string()),
+            (
+                "/global/rank/rankScore",
+                user.global.rank.rank_score.to_string(),
+            ),
+            ("/global/rank/rankName", user.global.rank.rank_name.clone()),
+            (
+                "/global/arena/rankScore",
+                user.global.arena.rank_score.to_string(),
+            ),
+            (
+                "/global/bans/isActive",
+                user.global.bans.is_active.to_string(),
+            ),
+            (
+                "/realtime/selectedLegend",
+                user.realtime.selected_legend.clone(),
+            ),
+            ("/total/kills/name", user.stats.br_kills.name.clone()),
+            ("/total/kills/value", user.stats.br_kills.value.to_string()),
+            (
+                "/total/damage/value",
+                user.stats.br_damage.value.to_string(),
+            ),
+            (
+                "/total/games_played/value",
+                user.stats.games_played.value.to_string(),
+            ),
+            ("/total/kd/value", user.stats.kd.value.clone()),
+        ]
+    }
+
+    fn game_fields(game: &data_types::ApexGame) -> Vec<(&'static str, String)> {
+        vec![
+            ("/uid", game.uid.clone()),
+            ("/legendPlayed", game.legend_played.clone()),
+            ("/gameEndTimestamp", game.game_end_timestamp.to_string()),
+            ("/BRScoreChange", game.br_score_change.to_string()),
+            ("/cosmetics/skinRarity", game.cosmetics.skin_rarity.clone()),
+        ]
+    }
+
+    fn map_rotation_fields(rotation: &data_types::ApexMapRotation) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "/battle_royale/current/map",
+                rotation.battle_royale.current.map.clone(),
+            ),
+            (
+                "/battle_royale/current/end",
+                rotation.battle_royale.current.end.to_string(),
+            ),
+            (
+                "/battle_royale/next/map",
+                rotation.battle_royale.next.map.clone(),
+            ),
+            ("/arenas/current/map", rotation.arenas.current.map.clone()),
+            ("/ranked/current/map", rotation.ranked.current.map.clone()),
+            (
+                "/arenasRanked/next/map",
+                rotation.arenas_ranked.next.map.clone(),
+            ),
+        ]
+    }
+
+    fn profile_fields(profile: &data_types::ApexProfile) -> Vec<(&'static str, String)> {
+        vec![
+            ("/name", profile.name.clone()),
+            ("/uid", profile.uid.clone()),
+            ("/pid", profile.pid.clone()),
+        ]
+    }
+
+    fn json_fixture(name: &str) -> serde_json::Value {
+        serde_json::from_str(&fixture(name)).unwrap()
+    }
+
+    fn any_mutation(
+    ) -> impl proptest::strategy::Strategy<Value = (proptest::sample::Index, Option<serde_json::Value>)>
+    {
+        (
+            proptest::arbitrary::any::<proptest::sample::Index>(),
+            proptest::option::weighted(0.8, any_json()),
+        )
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn mutated_user(mutation in any_mutation()) {
+            check_mutation(
+                &json_fixture("player_pc.json"),
+                mutation,
+                &["/global/name", "/global/uid"],
+                user_fields,
+            )?;
+        }
+
+        #[test]
+        fn mutated_game(mutation in any_mutation()) {
+            check_mutation(
+                &json_fixture("match_history.json")[0],
+                mutation,
+                &["/uid", "/gameEndTimestamp"],
+                game_fields,
+            )?;
+        }
+
+        #[test]
+        fn mutated_map_rotation(mutation in any_mutation()) {
+            check_mutation(
+                &json_fixture("map_rotation.json"),
+                mutation,
+                &["/battle_royale"],
+                map_rotation_fields,
+            )?;
+        }
+
+        #[test]
+        fn mutated_profile(mutation in any_mutation()) {
+            check_mutation(
+                &json_fixture("nametouid.json"),
+                mutation,
+                &["/name", "/uid"],
+                profile_fields,
+            )?;
+        }
+
+        #[test]
+        fn corrupted_bytes(flips in proptest::collection::vec((proptest::arbitrary::any::<proptest::sample::Index>(), proptest::arbitrary::any::<u8>()), 1..8)) {
+            for name in ["player_pc.json", "match_history.json", "map_rotation.json", "nametouid.json"] {
+                let mut bytes = fixture(name).into_bytes();
+                for (index, byte) in &flips {
+                    let i = index.index(bytes.len());
+                    bytes[i] = *byte;
+                }
+
+                // Must not panic, whatever the result
+                let _ = serde_json::from_slice::<data_types::ApexUser>(&bytes);
+                let _ = serde_json::from_slice::<Vec<data_types::ApexGame>>(&bytes);
+                let _ = serde_json::from_slice::<data_types::ApexMapRotation>(&bytes);
+                let _ = serde_json::from_slice::<data_types::ApexProfile>(&bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn lenient_fields() {
+        let mut doc = json_fixture("player_pc.json");
+        doc["global"]["level"] = "512".into();
+        doc["global"]["bans"]["isActive"] = 1.into();
+        doc["global"]["battlepass"]["level"] = 78.into();
+        doc["global"]["arena"] = "Unranked".into();
+        doc["realtime"]["isOnline"] = serde_json::Value::Null;
+        doc["total"]["kd"]["value"] = 1.5.into();
+        doc["total"]["damage"] = "3456789".into();
+
+        let user: data_types::ApexUser = serde_json::from_value(doc).unwrap();
+        assert_eq!(user.global.level, 512);
+        assert!(user.global.bans.is_active);
+        assert_eq!(user.global.battlepass.level, "78");
+        assert_eq!(user.global.arena.rank_score, 0);
+        assert_eq!(user.realtime.is_online, 0);
+        assert_eq!(user.global.rank.rank_score, 10842);
+        // Each stat falls back to its default on its own
+        assert_eq!(user.stats.kd.value, "1.5");
+        assert_eq!(user.stats.br_damage.name, "Missing attribute");
+        assert_eq!(user.stats.br_kills.value, 12345);
+
+        // Stats of other types are parsed leniently too
+        let stat: data_types::Stat<f64> =
+            serde_json::from_str(r#"{"name": "KD", "value": "1.5"}"#).unwrap();
+        assert_eq!(stat.value, 1.5);
+        let stat: data_types::Stat<u64> =
+            serde_json::from_str(r#"{"name": "Kills", "value": 12.0}"#).unwrap();
+        assert_eq!(stat.value, 12);
+        let stat: data_types::Stat<Option<i32>> =
+            serde_json::from_str(r#"{"name": "Wins", "value": [1]}"#).unwrap();
+        assert_eq!((stat.name.as_str(), stat.value), ("Wins", None));
+
+        // The error body of the API is still not taken for a player
         assert!(
-            print_data::<data_types::ApexProfile>(
-                apex_legends_api::get_uid_from_username_retry(
-                    String::from(&user_name),
-                    &api_key,
-                    true
-                )
-                .await,
-                |data| format!("Your UID is {}", data.uid),
+            serde_json::from_str::<data_types::ApexUser>(&fixture("player_unknown.json")).is_err()
+        );
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn cli() {
+        use apex_legends_api::mock::{MockMapRotation, MockRotation};
+
+        let server = server();
+        // An empty config file, so the one of the user is not read
+        let empty_config =
+            env::temp_dir().join(format!("apex-cli-empty-{}.toml", std::process::id()));
+        std::fs::write(&empty_config, "").unwrap();
+        let empty = empty_config.to_str().unwrap();
+        let apex = |args: &[&str], key: &str| {
+            std::process::Command::new(env!("CARGO_BIN_EXE_apex"))
+                .args(args)
+                .env("APEX_API_KEY", key)
+                .env("APEX_BASE_URL", server.url())
+                .env("APEX_CONFIG", "tests/fixtures/missing.toml")
+                .output()
+                .unwrap()
+        };
+        let stdout =
+            |output: &std::process::Output| String::from_utf8_lossy(&output.stdout).to_string();
+
+        let output = apex(&["user", "HeyImLifeline", "--config", empty], "test-key");
+        assert!(output.status.success());
+        assert!(stdout(&output).contains("Diamond 2 (10842 RP)"));
+        assert!(stdout(&output).contains("In the lobby"));
+
+        let output = apex(
+            &["user", "HeyImLifeline", "--json", "--config", empty],
+            "test-key",
+        );
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json["global"]["level"], 512);
+        // The field names of the data types, not the keys of the API
+        assert_eq!(json["global"]["rank"]["rank_score"], 10842);
+        assert!(json["global"]["rank"].get("rankScore").is_none());
+
+        let output = apex(
+            &[
+                "uid",
+                "HeyImLifeline",
+                "--platform",
+                "pc",
+                "--config",
+                empty,
+            ],
+            "test-key",
+        );
+        assert!(stdout(&output).contains("1000575311440"));
+
+        let output = apex(
+            &["games", "HeyImLifeline", "--limit", "2", "--config", empty],
+            "test-key",
+        );
+        assert_eq!(stdout(&output).lines().count(), 3);
+        assert!(stdout(&output).contains("+87"));
+
+        let output = apex(
+            &[
+                "games",
+                "1000575311440",
+                "--uid",
+                "--limit",
+                "2",
+                "--config",
+                empty,
+            ],
+            "test-key",
+        );
+        assert_eq!(stdout(&output).lines().count(), 3);
+
+        // Without --uid, digits are a name like any other
+        let output = apex(&["games", "1000575311440", "--config", empty], "test-key");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not found"));
+
+        let output = apex(&["maps", "--config", empty], "test-key");
+        assert!(stdout(&output).contains("Olympus"));
+        assert!(stdout(&output).contains("Phase Runner"));
+
+        let output = apex(&["user", "HeyImNobody", "--config", empty], "test-key");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not found"));
+
+        // Prints the rotation each time it is fetched, until it is stopped
+        let mut watch = std::process::Command::new(env!("CARGO_BIN_EXE_apex"))
+            .args(["maps", "--watch", "--json", "--config", empty])
+            .env("APEX_API_KEY", "test-key")
+            .env("APEX_BASE_URL", server.url())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        std::io::BufRead::read_line(
+            &mut std::io::BufReader::new(watch.stdout.take().unwrap()),
+            &mut line,
+        )
+        .unwrap();
+        watch.kill().unwrap();
+        watch.wait().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["battle_royale"]["current"]["map"], "Olympus");
+
+        // A new line when a map changes, from the rotation the watcher fetched
+        let rotation_server = MockServer::start();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        rotation_server.set_map_rotation(MockMapRotation {
+            arenas: MockRotation::new(
+                &["Phase Runner", "Party Crasher"],
+                now,
+                Duration::from_secs(1),
             ),
-            "get_uid_from_username_retry"
+            ..MockMapRotation::default()
+        });
+        let mut watch = std::process::Command::new(env!("CARGO_BIN_EXE_apex"))
+            .args(["maps", "--watch", "--json", "--config", empty])
+            .env("APEX_API_KEY", "test-key")
+            .env("APEX_BASE_URL", rotation_server.url())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut lines =
+            std::io::BufRead::lines(std::io::BufReader::new(watch.stdout.take().unwrap()));
+        let first: serde_json::Value =
+            serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        let second: serde_json::Value =
+            serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        watch.kill().unwrap();
+        watch.wait().unwrap();
+        assert_eq!(
+            first["arenas"]["next"]["map"],
+            second["arenas"]["current"]["map"]
         );
+        assert_eq!(rotation_server.hits(), 2);
+
+        // An explicit config file must exist
+        let output = apex(&["maps"], "test-key");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("missing.toml"));
+
+        // The key can also come from the config file
+        let config = env::temp_dir().join(format!("apex-cli-{}.toml", std::process::id()));
+        std::fs::write(&config, "api_key = \"test-key\"\nplatform = \"PS4\"\n").unwrap();
+        let output = apex(&["maps", "--config", config.to_str().unwrap()], "");
+        std::fs::remove_file(&config).unwrap();
+        std::fs::remove_file(&empty_config).unwrap();
+        assert!(output.status.success());
     }
 
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
     #[tokio::test]
-    async fn map_rotation() {
-        dotenv::dotenv().expect("Could not load .env file");
+    async fn map_rotation_watcher() {
+        use apex_legends_api::{
+            mock::{MockMapRotation, MockRotation},
+            watch::{MapRotationEvent, MapRotationWatcher, RotationMode},
+        };
+        use futures::StreamExt;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let server = MockServer::start();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        // Only the Arenas map changes during the test, every 2 seconds
+        server.set_map_rotation(MockMapRotation {
+            arenas: MockRotation::new(
+                &["Phase Runner", "Party Crasher"],
+                now,
+                Duration::from_secs(2),
+            ),
+            ..MockMapRotation::default()
+        });
+        server.set_response(Endpoint::MapRotation, 500, "");
 
-        let api_key = env::var("API_KEY").expect("Expected key API_KEY");
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .build();
+        let mut events = Box::pin(
+            MapRotationWatcher::new(client)
+                .delay(Duration::from_millis(100))
+                .retry_delay(Duration::from_millis(100))
+                .stream(),
+        );
 
-        assert!(
-            print_data::<data_types::ApexMapRotation>(
-                apex_legends_api::get_map_rotation_retry(&api_key, true).await,
-                |data| {
-                    format!(
-                        "The current ranked map is {}",
-                        data.arenas_ranked.current.map
-                    )
+        // Failed requests are retried
+        assert!(events.next().await.unwrap().is_err());
+        server.clear_response(Endpoint::MapRotation);
+
+        let started = match events.next().await.unwrap().unwrap() {
+            MapRotationEvent::Started(rotation) => rotation,
+            event => panic!("Unexpected event {:?}", event),
+        };
+        let change = match events.next().await.unwrap().unwrap() {
+            MapRotationEvent::Changed(change) => change,
+            event => panic!("Unexpected event {:?}", event),
+        };
+        assert_eq!(change.mode, RotationMode::Arenas);
+        assert_eq!(change.previous, started.arenas.current.map);
+        assert_eq!(change.map, started.arenas.next.map);
+        assert_eq!(change.end, Some(started.arenas.current.end + 2));
+
+        // One request per change, no polling in between
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+    #[tokio::test]
+    async fn presence_watcher() {
+        use apex_legends_api::watch::{PresenceChange, PresenceWatcher};
+        use futures::StreamExt;
+        use std::time::{Duration, Instant};
+
+        let server = MockServer::start();
+        let lifeline = MockPlayer::new("HeyImLifeline", Platform::Pc);
+        let wraith = MockPlayer::new("HeyImWraith", Platform::Xbox).online(false);
+        server.add_player(lifeline.clone().party_full(true));
+        server.add_player(wraith.clone());
+
+        // The watcher skips the cache, which would otherwise hide every change for an hour
+        let client = ApexClient::builder("test-key")
+            .base_url(server.url())
+            .cache(
+                apex_legends_api::cache::CacheConfig::empty()
+                    .ttl(Endpoint::Player, Duration::from_secs(3600)),
+            )
+            .build();
+        let started = Instant::now();
+        let mut events = Box::pin(
+            PresenceWatcher::new(client)
+                .add_player("HeyImLifeline", Platform::Pc)
+                .add_player("HeyImWraith", Platform::Xbox)
+                .add_player("HeyImNobody", Platform::Pc)
+                .interval(Duration::from_millis(200))
+                .requests_per_second(20.0)
+                .stream(),
+        );
+
+        // The first lookups only record the presence
+        let error = events.next().await.unwrap().unwrap_err();
+        assert_eq!(error.player, "HeyImNobody");
+        assert_eq!(error.platform, Platform::Pc);
+        assert!(matches!(error.error, Error::Api(_)));
+        assert!(error.to_string().starts_with("HeyImNobody on PC: "));
+        server.add_player(
+            lifeline
+                .party_full(true)
+                .in_game(true)
+                .selected_legend("Octane"),
+        );
+        server.add_player(wraith.online(true).can_join(true));
+
+        let mut changes = Vec::new();
+        while changes.len() < 4 {
+            match events.next().await.unwrap() {
+                Ok(event) => changes.push((event.player, event.change)),
+                // HeyImNobody is not found
+                Err(e) => assert_eq!(e.player, "HeyImNobody"),
+            }
+        }
+        let lifeline: Vec<_> = changes
+            .iter()
+            .filter(|(player, _)| player == "HeyImLifeline")
+            .map(|(_, change)| change.clone())
+            .collect();
+        let wraith: Vec<_> = changes
+            .iter()
+            .filter(|(player, _)| player == "HeyImWraith")
+            .map(|(_, change)| change.clone())
+            .collect();
+        assert_eq!(
+            lifeline,
+            vec![
+                PresenceChange::EnteredMatch,
+                PresenceChange::ChangedLegend {
+                    previous: "Lifeline".to_string(),
+                    legend: "Octane".to_string()
                 },
-            ),
-            "get_map_rotation_retry"
+            ]
         );
-        assert!(
-            print_data::<data_types::ApexMapRotation>(
-                apex_legends_api::get_map_rotation_retry(&api_key, true).await,
-                |data| {
-                    format!(
-                        "The current ranked map is {}",
-                        data.arenas_ranked.current.map
-                    )
+        assert_eq!(
+            wraith,
+            vec![PresenceChange::CameOnline, PresenceChange::PartyJoinable]
+        );
+
+        // Two rounds of 3 players, spaced out by the rate limit
+        assert!(started.elapsed() >= Duration::from_millis(250));
+        assert!(server.hits() <= 7);
+    }
+
+    #[test]
+    fn session_tracker() {
+        use apex_legends_api::{
+            games::GameMode,
+            session::{SessionChange, SessionTracker, Tier},
+        };
+
+        // Diamond 2 with 10842 RP, Platinum 1 with 4321 AP, level 512 at 37%
+        let mut user: data_types::ApexUser = parse("player_pc.json");
+        let mut session = SessionTracker::new(&user);
+
+        user.global.rank.rank_score += 120;
+        user.global.rank.rank_division = 1;
+        user.global.level = 513;
+        user.global.to_next_level_percent = 5;
+        let changes = session.record(&user);
+        assert_eq!(
+            changes,
+            vec![
+                SessionChange::Points {
+                    mode: GameMode::BattleRoyale,
+                    change: 120,
+                    score: 10962
                 },
-            ),
-            "get_map_rotation_retry"
+                SessionChange::Promoted {
+                    mode: GameMode::BattleRoyale,
+                    from: Tier {
+                        name: "Diamond".to_string(),
+                        division: 2
+                    },
+                    to: Tier {
+                        name: "Diamond".to_string(),
+                        division: 1
+                    },
+                },
+                SessionChange::LevelUp { from: 512, to: 513 },
+            ]
+        );
+
+        user.global.arena.rank_score -= 30;
+        user.global.arena.rank_name = "Gold".to_string();
+        let changes = session.record(&user);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(
+            &changes[1],
+            SessionChange::Demoted { mode: GameMode::Arenas, to, .. } if to.to_string() == "Gold 1"
+        ));
+
+        // Nothing changed
+        assert!(session.record(&user).is_empty());
+
+        let summary = session.summary();
+        assert_eq!(summary.battle_royale.points, 120);
+        assert_eq!(summary.battle_royale.start.to_string(), "Diamond 2");
+        assert_eq!(summary.battle_royale.current.to_string(), "Diamond 1");
+        assert_eq!(summary.battle_royale.promotions, 1);
+        assert_eq!(summary.arenas.points, -30);
+        assert_eq!(summary.arenas.score, 4291);
+        assert_eq!(summary.arenas.demotions, 1);
+        assert_eq!(summary.levels, 1);
+        assert_eq!(summary.level_progress, 68);
+        assert_eq!(summary.snapshots, 4);
+
+        // Master has no division
+        let master = Tier {
+            name: "Master".to_string(),
+            division: 0,
+        };
+        assert_eq!(master.to_string(), "Master");
+        assert_eq!(
+            master.compare(&summary.battle_royale.current),
+            Some(std::cmp::Ordering::Greater)
+        );
+        assert_eq!(
+            master.compare(&Tier {
+                name: "Legend".to_string(),
+                division: 1
+            }),
+            None
         );
     }
 }